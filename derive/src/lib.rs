@@ -1,6 +1,6 @@
-use darling::FromDeriveInput;
+use darling::{FromDeriveInput, FromMeta};
 use proc_macro::{self, TokenStream};
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{parse_macro_input, DeriveInput};
 
 #[derive(FromDeriveInput, Default)]
@@ -8,30 +8,315 @@ use syn::{parse_macro_input, DeriveInput};
 struct Opts {
     #[darling(rename = "Counter")]
     counter: Option<syn::TypePath>,
+    tags: Option<Tags>,
+    meta: Option<Meta>,
+    weak: bool,
+    on_first_touch: Option<syn::Path>,
+    per_param: Option<Param>,
+    census: bool,
+    hook: Option<Hook>,
+    state_of: Option<syn::Path>,
+}
+
+/// A list of string literals, e.g. `tags("net", "io")`.
+#[derive(Default)]
+struct Tags(Vec<syn::LitStr>);
+
+impl FromMeta for Tags {
+    fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+        items
+            .iter()
+            .map(|item| match item {
+                syn::NestedMeta::Lit(syn::Lit::Str(tag)) => Ok(tag.clone()),
+                _ => Err(darling::Error::unexpected_type("non-string literal").with_span(item)),
+            })
+            .collect::<darling::Result<_>>()
+            .map(Tags)
+    }
+}
+
+/// A list of string key-value pairs, e.g. `meta(owner = "team-net")`.
+#[derive(Default)]
+struct Meta(Vec<(String, syn::LitStr)>);
+
+impl FromMeta for Meta {
+    fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+        items
+            .iter()
+            .map(|item| match item {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(value),
+                    ..
+                })) => match path.get_ident() {
+                    Some(key) => Ok((key.to_string(), value.clone())),
+                    None => Err(darling::Error::unexpected_type("path").with_span(path)),
+                },
+                _ => Err(darling::Error::unexpected_type("non-key-value").with_span(item)),
+            })
+            .collect::<darling::Result<_>>()
+            .map(Meta)
+    }
+}
+
+/// A single generic type parameter, e.g. `per_param(T)`.
+struct Param(syn::Ident);
+
+impl FromMeta for Param {
+    fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+        match items {
+            [syn::NestedMeta::Meta(syn::Meta::Path(path))] => path
+                .get_ident()
+                .cloned()
+                .map(Param)
+                .ok_or_else(|| darling::Error::unexpected_type("path").with_span(path)),
+            _ => Err(darling::Error::too_many_items(1)),
+        }
+    }
+}
+
+/// Where the population count of a type is updated.
+#[derive(FromMeta)]
+enum Hook {
+    /// In a generated `tabulated` constructor helper and a generated `Drop`
+    /// impl, rather than by an `Instance<Self>` field.
+    #[darling(rename = "drop")]
+    Drop,
 }
 
 #[proc_macro_derive(Tabulate, attributes(Tabulate))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input);
     let opts = Opts::from_derive_input(&input).expect("Wrong options");
-    let DeriveInput { ident, .. } = input;
+    let DeriveInput { ident, vis, .. } = input;
 
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let generics_empty = input.generics.params.is_empty();
 
     let counter_ty = match opts.counter {
         Some(counter_ty) => quote! { #counter_ty },
-        None => quote! { type_census::counter::RelaxedCounter },
+        None => quote! { type_census::counter::DefaultCounter },
+    };
+
+    let tags = opts.tags.unwrap_or_default().0;
+    let (meta_keys, meta_values): (Vec<_>, Vec<_>) =
+        opts.meta.unwrap_or_default().0.into_iter().unzip();
+
+    let weak_impl = opts.weak.then(|| {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics type_census::TabulateWeak for #ident #ty_generics #where_clause {
+                fn weak_counter() -> &'static #counter_ty {
+                    static COUNTER: #counter_ty = <#counter_ty as type_census::counter::Counter>::ZERO;
+                    &COUNTER
+                }
+            }
+        }
+    });
+
+    let first_touch = opts.on_first_touch.map(|hook| {
+        quote! {
+            static FIRST_TOUCH: std::sync::Once = std::sync::Once::new();
+            FIRST_TOUCH.call_once(|| #hook(<Self as type_census::Tabulate>::metadata()));
+        }
+    });
+
+    let census_ident = format_ident!("{}Census", ident);
+
+    let counter = match (&opts.per_param, opts.census) {
+        (Some(_), true) => panic!("`census` cannot be combined with `per_param`"),
+        (Some(Param(param)), false) => quote! {
+            Self::__type_census_counters().get::<#param>()
+        },
+        (None, true) => quote! {
+            #census_ident.counter()
+        },
+        (None, false) => quote! {
+            static COUNTER: #counter_ty = <#counter_ty as type_census::counter::Counter>::ZERO;
+            &COUNTER
+        },
     };
 
+    let census = opts.census.then(|| {
+        quote! {
+            #[doc = concat!("A zero-sized façade for querying the population of [`", stringify!(#ident), "`].")]
+            #[derive(Clone, Copy, Debug, Default)]
+            #vis struct #census_ident;
+
+            impl #census_ident {
+                #[doc = concat!("Produces a reference to the counter tracking instances of [`", stringify!(#ident), "`].")]
+                #vis fn counter(&self) -> &'static #counter_ty {
+                    static COUNTER: #counter_ty = <#counter_ty as type_census::counter::Counter>::ZERO;
+                    &COUNTER
+                }
+
+                #[doc = concat!("Produces the number of extant instances of [`", stringify!(#ident), "`].")]
+                #vis fn instances(&self) -> <#counter_ty as type_census::counter::Counter>::Primitive {
+                    type_census::counter::Counter::fetch(self.counter())
+                }
+
+                #[doc = concat!("Produces the most bytes ever attributed to the instances of [`", stringify!(#ident), "`], if its counter tracks bytes.")]
+                #vis fn peak(&self) -> Option<usize> {
+                    type_census::counter::Counter::fetch_peak_bytes(self.counter())
+                }
+
+                #[doc = concat!("Produces the number of bytes attributed to the extant instances of [`", stringify!(#ident), "`], if its counter tracks bytes.")]
+                #vis fn bytes(&self) -> Option<usize> {
+                    type_census::counter::Counter::fetch_bytes(self.counter())
+                }
+
+                #[doc = concat!("Invokes `callback` whenever the population of [`", stringify!(#ident), "`] crosses a multiple of `granularity`, until the returned subscription is dropped.")]
+                #[doc = ""]
+                #[doc = "See [`type_census::census::subscribe`]."]
+                #vis fn watch<F>(
+                    &self,
+                    granularity: std::num::NonZeroUsize,
+                    callback: F,
+                ) -> type_census::census::Subscription
+                where
+                    F: Fn(&type_census::census::Event) + Send + Sync + 'static,
+                {
+                    type_census::census::subscribe(granularity, move |event| {
+                        if event.metadata.name == stringify!(#ident)
+                            && event.metadata.module_path == module_path!()
+                        {
+                            callback(event);
+                        }
+                    })
+                }
+            }
+
+            #[automatically_derived]
+            impl type_census::Census for #census_ident {
+                type Counter = #counter_ty;
+                fn counter(&self) -> &'static #counter_ty {
+                    #census_ident::counter(self)
+                }
+            }
+        }
+    });
+
+    let hook = opts.hook.map(|Hook::Drop| {
+        quote! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                #[doc = concat!("Counts `self` as an extant instance of [`", stringify!(#ident), "`].")]
+                #vis fn tabulated(self) -> Self {
+                    type_census::__private::increment::<Self>();
+                    self
+                }
+            }
+
+            #[automatically_derived]
+            impl #impl_generics Drop for #ident #ty_generics #where_clause {
+                fn drop(&mut self) {
+                    type_census::__private::decrement::<Self>();
+                }
+            }
+        }
+    });
+
+    // With `per_param`, the counters of every instantiation share one
+    // static, which is reached from both `counter` and `ensure_registered`.
+    let per_param_counters = opts.per_param.as_ref().map(|_| {
+        quote! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                #[doc(hidden)]
+                fn __type_census_counters() -> &'static type_census::counter::PerType<#counter_ty> {
+                    static COUNTERS: type_census::counter::PerType<#counter_ty> =
+                        type_census::counter::PerType::new();
+                    &COUNTERS
+                }
+            }
+        }
+    });
+
+    // Only non-generic types are known to be `'static`, and so to have a
+    // type id to register.
+    let register_self = if generics_empty {
+        quote! { type_census::registry().register::<Self>() }
+    } else {
+        quote! { type_census::__private::register::<Self>() }
+    };
+    // With `per_param`, each parameter has its own counter to register, so
+    // a single flag can't track registration; each counter has its own.
+    let ensure_registered = match &opts.per_param {
+        Some(Param(param)) => quote! {
+            fn ensure_registered() {
+                Self::__type_census_counters().ensure_registered::<#param, Self>();
+            }
+        },
+        None => quote! {
+            fn ensure_registered() {
+                static REGISTERED: std::sync::atomic::AtomicBool =
+                    std::sync::atomic::AtomicBool::new(false);
+                if !REGISTERED.swap(true, std::sync::atomic::Ordering::Relaxed) {
+                    #register_self;
+                }
+            }
+        },
+    };
+
+    let per_param = opts.per_param.as_ref().map(|Param(param)| {
+        quote! {
+            .per_param(std::any::type_name::<#param>())
+        }
+    });
+
+    let state_of = opts.state_of.as_ref().map(|machine| {
+        quote! {
+            .state_of(std::any::type_name::<#machine>())
+        }
+    });
+
+    let state_impl = opts.state_of.as_ref().map(|machine| {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics type_census::State for #ident #ty_generics #where_clause {
+                type Machine = #machine;
+            }
+        }
+    });
+
+    // Only non-generic types name a single counter that can be registered
+    // ahead of time.
+    let register = generics_empty.then(|| {
+        quote! {
+            type_census::__register!(#ident);
+        }
+    });
+
+    let generic = !generics_empty;
+
     let output = quote! {
         #[automatically_derived]
         impl #impl_generics type_census::Tabulate for #ident #ty_generics #where_clause {
             type Counter = #counter_ty;
             fn counter() -> &'static #counter_ty {
-                static COUNTER: #counter_ty = <#counter_ty as type_census::counter::Counter>::ZERO;
-                &COUNTER
+                #first_touch
+                #counter
+            }
+            #ensure_registered
+            fn metadata() -> type_census::Metadata {
+                type_census::Metadata::of::<Self>()
+                    .named(stringify!(#ident), module_path!())
+                    .tagged(&[#(#tags),*])
+                    .annotated(&[#((#meta_keys, #meta_values)),*])
+                    #per_param
+                    #state_of
             }
         }
+        #per_param_counters
+        #state_impl
+        #weak_impl
+        #census
+        #hook
+        #register
+        type_census::__declare!(
+            #ident,
+            #generic,
+            &[#(#tags),*],
+            &[#((#meta_keys, #meta_values)),*]
+        );
     };
     output.into()
 }
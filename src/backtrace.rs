@@ -0,0 +1,143 @@
+//! Where the instances of a type were created.
+//!
+//! With the `capture-backtrace` feature, every [`Instance::new`] captures a
+//! backtrace, and counts the instance against that backtrace in a table for
+//! its type. When a leak is suspected, [`created`] and [`dump`] show where
+//! the instances of a type were created, and how many were created at each.
+//!
+//! [`Instance`](crate::Instance) stays zero-sized, so a dropped instance
+//! can't be traced back to its backtrace: the table counts creations, not
+//! survivors. Compare it against the type's population, or [`clear`] it
+//! once the program reaches a steady state, so that it counts only the
+//! instances created since.
+//!
+//! Nothing is captured when counting is [disabled](crate#disabling).
+//!
+//! Backtraces are captured with [`Backtrace::force_capture`], regardless of
+//! `RUST_BACKTRACE`, and rendered to tell them apart, so constructing
+//! instances becomes much slower; enable this feature only while hunting
+//! leaks.
+//!
+//! ## Example
+//! ```
+//! use type_census::{backtrace, Instance, Tabulate};
+//!
+//! #[derive(Tabulate)]
+//! pub struct Foo {
+//!     _instance: Instance<Self>,
+//! }
+//!
+//! let dropped = Foo { _instance: Instance::new() };
+//! drop(dropped);
+//! backtrace::clear::<Foo>();
+//!
+//! let leaked = Foo { _instance: Instance::new() };
+//! let created = backtrace::created::<Foo>();
+//! assert_eq!(created.iter().map(|(_, count)| count).sum::<u64>(), 1);
+//! backtrace::dump::<Foo>(std::io::stderr())?;
+//! # drop(leaked);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! [`Instance::new`]: crate::Instance::new
+
+use crate::{registry, Tabulate};
+use std::backtrace::Backtrace;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Each distinct creation backtrace, by its rendering, and the number of
+/// instances created with it, of each type, by the address of its counter.
+type Table = BTreeMap<usize, BTreeMap<String, (Arc<Backtrace>, u64)>>;
+
+static CREATED: Mutex<Table> = Mutex::new(BTreeMap::new());
+
+fn lock() -> MutexGuard<'static, Table> {
+    CREATED.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Produces the key of `T` in [`CREATED`].
+///
+/// Types sharing a counter (such as the instantiations of a generic type)
+/// share a table.
+fn key<T>() -> usize
+where
+    T: Tabulate,
+{
+    T::counter() as *const T::Counter as usize
+}
+
+/// Records the backtrace of a new instance of `T`.
+#[inline(always)]
+pub(crate) fn capture<T>()
+where
+    T: Tabulate,
+{
+    if !cfg!(type_census_disabled) {
+        record::<T>();
+    }
+}
+
+#[cold]
+fn record<T>()
+where
+    T: Tabulate,
+{
+    let backtrace = Backtrace::force_capture();
+    let rendered = backtrace.to_string();
+    let mut created = lock();
+    let (_, count) = created
+        .entry(key::<T>())
+        .or_default()
+        .entry(rendered)
+        .or_insert_with(|| (Arc::new(backtrace), 0));
+    *count += 1;
+}
+
+/// Produces each distinct backtrace with which instances of `T` were
+/// created, and the number of instances created with it, in descending
+/// order of count.
+///
+/// Only instances constructed with [`Instance`](crate::Instance) are
+/// recorded.
+pub fn created<T>() -> Vec<(Arc<Backtrace>, u64)>
+where
+    T: Tabulate,
+{
+    let mut created: Vec<_> = lock()
+        .get(&key::<T>())
+        .into_iter()
+        .flat_map(|backtraces| backtraces.values().cloned())
+        .collect();
+    created.sort_by(|(_, a), (_, b)| b.cmp(a));
+    created
+}
+
+/// Forgets the recorded backtraces of `T`, so that only the instances
+/// created from now on are counted.
+pub fn clear<T>()
+where
+    T: Tabulate,
+{
+    lock().remove(&key::<T>());
+}
+
+/// Writes the population of `T`, followed by each distinct backtrace with
+/// which instances of `T` were created, most frequent first, to `writer`.
+pub fn dump<T>(mut writer: impl Write) -> io::Result<()>
+where
+    T: Tabulate,
+{
+    let created = created::<T>();
+    let name = std::any::type_name::<T>();
+    writeln!(
+        writer,
+        "{} live instances of {name}",
+        registry::count::<T>()
+    )?;
+    for (backtrace, count) in &created {
+        writeln!(writer, "{count} created at:\n{backtrace}")?;
+    }
+    writer.flush()
+}
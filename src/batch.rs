@@ -0,0 +1,142 @@
+//! Counting many instances at once.
+
+use crate::counter::Counter;
+use crate::{census, Tabulate};
+use num_traits::NumCast;
+use std::marker::PhantomData;
+
+/// The primitive type of the counter of `T`.
+type Primitive<T> = <<T as Tabulate>::Counter as Counter>::Primitive;
+
+/// A guard that tracks the lifetimes of `n` instances of `T` at once.
+///
+/// Constructing an `Instances<T>` adds `n` to the population count of `T`,
+/// and dropping it subtracts `n`, each with a single counter update. This
+/// suits containers that logically own many counted items, such as arena
+/// chunks and bulk buffers:
+///
+/// ```
+/// use type_census::{Instance, Instances, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct Node {
+///     value: u64,
+///     _instance: Instance<Self>,
+/// }
+///
+/// /// A chunk of `Node`s stored without their `Instance` fields.
+/// pub struct Chunk {
+///     values: Vec<u64>,
+///     _instances: Instances<Node>,
+/// }
+///
+/// impl Chunk {
+///     fn new(values: Vec<u64>) -> Self {
+///         let _instances = Instances::new(values.len());
+///         Self { values, _instances }
+///     }
+/// }
+///
+/// let chunk = Chunk::new(vec![0; 64]);
+/// let _node = Node { value: 1, _instance: Instance::new() };
+/// assert_eq!(Node::instances(), 65);
+///
+/// drop(chunk);
+/// assert_eq!(Node::instances(), 1);
+/// ```
+pub struct Instances<T>
+where
+    T: Tabulate,
+    Primitive<T>: NumCast,
+{
+    n: usize,
+    _tabulated: PhantomData<T>,
+}
+
+impl<T> Instances<T>
+where
+    T: Tabulate,
+    Primitive<T>: NumCast,
+{
+    /// Constructs a new `Instances<T>`, representing the extant lifetimes of
+    /// `n` instances of `T`.
+    ///
+    /// # Panics
+    /// Panics if `n` is not representable by the counter of `T`.
+    #[inline(always)]
+    pub fn new(n: usize) -> Self {
+        #[cfg(feature = "lazy-register")]
+        T::ensure_registered();
+        census::add::<T>(primitive::<T>(n), isize::try_from(n).unwrap_or(isize::MAX));
+        Self {
+            n,
+            _tabulated: PhantomData,
+        }
+    }
+
+    /// Produces the number of instances represented by this guard.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Produces `true` if this guard represents no instances.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+}
+
+/// Converts `n` to the primitive type of the counter of `T`.
+#[inline(always)]
+#[track_caller]
+fn primitive<T>(n: usize) -> Primitive<T>
+where
+    T: Tabulate,
+    Primitive<T>: NumCast,
+{
+    match NumCast::from(n) {
+        Some(n) => n,
+        None => panic!(
+            "{n} instances are not representable by `{}`",
+            std::any::type_name::<T::Counter>()
+        ),
+    }
+}
+
+impl<T> std::fmt::Debug for Instances<T>
+where
+    T: Tabulate,
+    Primitive<T>: NumCast,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("n", &self.n)
+            .finish()
+    }
+}
+
+impl<T> Clone for Instances<T>
+where
+    T: Tabulate,
+    Primitive<T>: NumCast,
+{
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self::new(self.n)
+    }
+}
+
+impl<T> Drop for Instances<T>
+where
+    T: Tabulate,
+    Primitive<T>: NumCast,
+{
+    #[inline(always)]
+    fn drop(&mut self) {
+        census::sub::<T>(
+            primitive::<T>(self.n),
+            isize::try_from(self.n).unwrap_or(isize::MAX),
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Deferred instance counting.
+
+use crate::{Instance, Tabulate};
+
+/// A slot that starts uncounted and begins tracking the lifetime of an
+/// instance of `T` once [armed](InstanceCell::arm).
+///
+/// This is useful for builders and two-phase initialization, where an
+/// object should only be counted once it becomes "real". Dropping an armed
+/// `InstanceCell<T>` decrements the population count of `T`; dropping an
+/// unarmed one does nothing.
+///
+/// ```
+/// use type_census::{Instance, InstanceCell, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct Foo {
+///     _instance: InstanceCell<Self>,
+/// }
+///
+/// let mut foo = Foo { _instance: InstanceCell::new() };
+/// assert_eq!(Foo::instances(), 0);
+///
+/// foo._instance.arm();
+/// assert_eq!(Foo::instances(), 1);
+///
+/// drop(foo);
+/// assert_eq!(Foo::instances(), 0);
+/// ```
+pub struct InstanceCell<T>
+where
+    T: Tabulate,
+{
+    instance: Option<Instance<T>>,
+}
+
+impl<T> InstanceCell<T>
+where
+    T: Tabulate,
+{
+    /// Constructs a new, unarmed `InstanceCell<T>`.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self { instance: None }
+    }
+
+    /// Arms this cell, incrementing the population count of `T`.
+    ///
+    /// Arming an already-armed cell does nothing.
+    #[inline(always)]
+    #[track_caller]
+    pub fn arm(&mut self) {
+        if self.instance.is_none() {
+            self.instance = Some(Instance::new());
+        }
+    }
+
+    /// Disarms this cell, decrementing the population count of `T`.
+    ///
+    /// Disarming an unarmed cell does nothing.
+    #[inline(always)]
+    pub fn disarm(&mut self) {
+        self.instance = None;
+    }
+
+    /// Produces `true` if this cell is armed.
+    #[inline(always)]
+    pub fn is_armed(&self) -> bool {
+        self.instance.is_some()
+    }
+}
+
+impl<T> From<Instance<T>> for InstanceCell<T>
+where
+    T: Tabulate,
+{
+    /// Constructs an armed `InstanceCell<T>` from an existing instance.
+    #[inline(always)]
+    fn from(instance: Instance<T>) -> Self {
+        Self {
+            instance: Some(instance),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for InstanceCell<T>
+where
+    T: Tabulate,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("armed", &self.is_armed())
+            .finish()
+    }
+}
+
+impl<T> Default for InstanceCell<T>
+where
+    T: Tabulate,
+{
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for InstanceCell<T>
+where
+    T: Tabulate,
+{
+    /// Clones this cell. The clone is armed if and only if this cell is.
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            instance: self.instance.clone(),
+        }
+    }
+}
@@ -0,0 +1,946 @@
+//! Operations over the census of tabulated types.
+
+mod filter;
+mod frame;
+pub mod grafana;
+mod named;
+pub mod pprof;
+pub mod recorder;
+#[cfg(all(feature = "uds", unix))]
+pub mod socket;
+
+pub use filter::{Filter, ParseFilterError, ENV};
+pub use frame::{begin_frame, end_frame, scoped_baseline, Baseline, Change, Changes};
+pub use named::{counter, named_count, named_report, NamedInstance};
+
+use crate::clock::{Clock, SystemClock};
+use crate::counter::Counter;
+use crate::registry::{self, registry, Entry};
+use crate::snapshot::{ProcessMemory, Snapshot, SortBy};
+use crate::{Metadata, Tabulate};
+use num_traits::Zero;
+use std::any::{Any, TypeId};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+
+/// The primitive type of the counter of `T`.
+type Primitive<T> = <<T as Tabulate>::Counter as Counter>::Primitive;
+
+/// Takes a [`Snapshot`] of every type in the global
+/// [registry](crate::registry).
+///
+/// ```
+/// use type_census::{census, registry, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<Foo>();
+///
+/// let _foo = Foo { _instance: Instance::new() };
+/// let snapshot = census::report();
+/// assert_eq!(snapshot.get(concat!(module_path!(), "::Foo")), Some(1));
+/// ```
+pub fn report() -> Snapshot {
+    report_with(ReportOptions::default())
+}
+
+/// Takes a [`Snapshot`] of every type in the global
+/// [registry](crate::registry), configured by `options`.
+///
+/// ```
+/// use type_census::{census, registry, Instance, Tabulate};
+/// use type_census::census::ReportOptions;
+///
+/// #[derive(Tabulate)]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// #[derive(Tabulate)]
+/// pub struct Bar {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<Foo>();
+/// registry().register::<Bar>();
+///
+/// let _foo = Foo { _instance: Instance::new() };
+/// let snapshot = census::report_with(ReportOptions::new().include_zero(false));
+/// let foo = concat!(module_path!(), "::Foo");
+/// assert_eq!(snapshot.iter().collect::<Vec<_>>(), [(foo, 1)]);
+/// ```
+pub fn report_with(options: ReportOptions) -> Snapshot {
+    let snapshot = Snapshot::of_entries(
+        registry()
+            .entries()
+            .map(|entry| (entry, entry.count()))
+            .filter(|(_, count)| options.include_zero || *count != 0),
+    );
+    if options.process_memory {
+        snapshot.with_process_memory(ProcessMemory::current())
+    } else {
+        snapshot
+    }
+}
+
+/// Configuration for [`report_with`].
+#[derive(Clone, Copy, Debug)]
+pub struct ReportOptions {
+    include_zero: bool,
+    process_memory: bool,
+}
+
+impl ReportOptions {
+    /// Constructs the default options, which include every registered type,
+    /// and not the memory use of the process.
+    pub const fn new() -> Self {
+        Self {
+            include_zero: true,
+            process_memory: false,
+        }
+    }
+
+    /// Sets whether types with no extant instances appear in the report.
+    ///
+    /// Defaults to `true`.
+    pub const fn include_zero(self, include_zero: bool) -> Self {
+        Self {
+            include_zero,
+            ..self
+        }
+    }
+
+    /// Sets whether the report carries the
+    /// [memory use](Snapshot::process_memory) of the process, so that the
+    /// populations of types can be compared against it.
+    ///
+    /// Defaults to `false`.
+    pub const fn process_memory(self, process_memory: bool) -> Self {
+        Self {
+            process_memory,
+            ..self
+        }
+    }
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Queries the global [registry](crate::registry) for the types whose paths
+/// match the glob `pattern`.
+///
+/// In the pattern, `*` matches any sequence of characters within one path
+/// segment, `**` matches any sequence of characters, across segments, and
+/// `?` matches any one character. Every other character matches itself. So
+/// `my_app::net::*` matches the types defined directly in `my_app::net`,
+/// `my_app::net::**` also matches those in its submodules, and `**Error`
+/// matches every type whose name ends with `Error`.
+///
+/// The matching types are produced in registration order, unless the query
+/// is [sorted](Query::sorted), and may be [limited](Query::limit) to the
+/// first few. Matching takes time proportional to the product of the
+/// lengths of the pattern and the path. With the `regex` feature, types may
+/// be queried by regular expression instead, with [`query_regex`].
+///
+/// ```
+/// use type_census::snapshot::SortBy;
+/// use type_census::{census, registry, Instance, Tabulate};
+///
+/// mod net {
+///     use type_census::{Instance, Tabulate};
+///
+///     #[derive(Clone, Tabulate)]
+///     pub struct Connection {
+///         pub _instance: Instance<Self>,
+///     }
+///
+///     #[derive(Clone, Tabulate)]
+///     pub struct Packet {
+///         pub _instance: Instance<Self>,
+///     }
+/// }
+///
+/// registry().register::<net::Connection>();
+/// registry().register::<net::Packet>();
+///
+/// let _connections = vec![net::Connection { _instance: Instance::new() }; 2];
+/// let _packets = vec![net::Packet { _instance: Instance::new() }; 5];
+///
+/// let pattern = concat!(module_path!(), "::net::*");
+/// let top = census::query(pattern).sorted(SortBy::Count).limit(1).run();
+/// let packet = concat!(module_path!(), "::net::Packet");
+/// assert_eq!(top.iter().collect::<Vec<_>>(), [(packet, 5)]);
+/// ```
+pub fn query(pattern: &str) -> Query<'_> {
+    Query {
+        pattern: Pattern::Glob(pattern),
+        order: None,
+        limit: None,
+    }
+}
+
+/// Queries the global [registry](crate::registry) for the types whose paths
+/// match the regular expression `pattern`, with the syntax of the
+/// [`regex`](https://docs.rs/regex) crate.
+///
+/// The pattern may match anywhere in a path, unless anchored with `^` and
+/// `$`. Otherwise, this is like [`query`].
+///
+/// Only available with the `regex` feature.
+///
+/// ```
+/// use type_census::{census, registry, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct IoError {
+///     _instance: Instance<Self>,
+/// }
+///
+/// #[derive(Tabulate)]
+/// pub struct ParseError {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<IoError>();
+/// registry().register::<ParseError>();
+///
+/// let _error = IoError { _instance: Instance::new() };
+///
+/// let errors = census::query_regex(r"::(Io|Net)Error$")?.run();
+/// let io_error = concat!(module_path!(), "::IoError");
+/// assert_eq!(errors.iter().collect::<Vec<_>>(), [(io_error, 1)]);
+///
+/// assert!(census::query_regex("(").is_err());
+/// # Ok::<(), type_census::census::ParseRegexError>(())
+/// ```
+#[cfg(feature = "regex")]
+pub fn query_regex(pattern: &str) -> Result<Query<'static>, ParseRegexError> {
+    let regex = regex_automata::meta::Regex::new(pattern)
+        .map_err(|error| ParseRegexError(error.to_string()))?;
+    Ok(Query {
+        pattern: Pattern::Regex(regex),
+        order: None,
+        limit: None,
+    })
+}
+
+/// The error produced by [`query_regex`] for an invalid regular expression.
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseRegexError(String);
+
+#[cfg(feature = "regex")]
+impl std::fmt::Display for ParseRegexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid type census query: {}", self.0)
+    }
+}
+
+#[cfg(feature = "regex")]
+impl std::error::Error for ParseRegexError {}
+
+/// A query of the global [registry](crate::registry), constructed by
+/// [`query`] or [`query_regex`].
+#[derive(Clone, Debug)]
+#[must_use = "a `Query` does nothing until it is run"]
+pub struct Query<'a> {
+    pattern: Pattern<'a>,
+    order: Option<SortBy<'a>>,
+    limit: Option<usize>,
+}
+
+/// The pattern matched against paths by a [`Query`].
+#[derive(Clone, Debug)]
+enum Pattern<'a> {
+    Glob(&'a str),
+    #[cfg(feature = "regex")]
+    Regex(regex_automata::meta::Regex),
+}
+
+impl Pattern<'_> {
+    /// Produces `true` if `path` matches this pattern.
+    fn is_match(&self, path: &str) -> bool {
+        match self {
+            Pattern::Glob(pattern) => glob(pattern, path),
+            #[cfg(feature = "regex")]
+            Pattern::Regex(regex) => regex.is_match(path),
+        }
+    }
+}
+
+impl<'a> Query<'a> {
+    /// Sorts the matching types by `order`.
+    pub fn sorted(self, order: SortBy<'a>) -> Self {
+        Self {
+            order: Some(order),
+            ..self
+        }
+    }
+
+    /// Produces at most the first `limit` matching types, after sorting.
+    pub fn limit(self, limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Runs this query, producing a [`Snapshot`] of the matching types.
+    pub fn run(self) -> Snapshot {
+        let mut snapshot = Snapshot::of_entries(
+            registry()
+                .filter(|entry| self.pattern.is_match(entry.name()))
+                .map(|entry| (entry, entry.count())),
+        );
+        if let Some(order) = self.order {
+            snapshot.sort(order);
+        }
+        if let Some(limit) = self.limit {
+            snapshot.truncate(limit);
+        }
+        snapshot
+    }
+}
+
+/// An element of a glob pattern.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Token {
+    /// `**`: any sequence of characters.
+    Any,
+    /// `*`: any sequence of characters within one path segment.
+    Segment,
+    /// `?`: any one character.
+    One,
+    /// Any other character, which matches itself.
+    Char(char),
+}
+
+/// Produces `true` if `path` matches the glob `pattern`, as described by
+/// [`query`].
+///
+/// This simulates the pattern as a nondeterministic automaton, whose states
+/// are the positions in the pattern, so it takes time proportional to the
+/// product of the lengths of the pattern and the path.
+fn glob(pattern: &str, path: &str) -> bool {
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '*' if chars.next_if_eq(&'*').is_some() => Token::Any,
+            '*' => Token::Segment,
+            '?' => Token::One,
+            c => Token::Char(c),
+        });
+    }
+    // Marks every state reachable from an active state without consuming a
+    // character: a star may match nothing.
+    let close = |states: &mut [bool]| {
+        for i in 0..tokens.len() {
+            if states[i] && matches!(tokens[i], Token::Any | Token::Segment) {
+                states[i + 1] = true;
+            }
+        }
+    };
+    let mut states = vec![false; tokens.len() + 1];
+    let mut next = states.clone();
+    states[0] = true;
+    close(&mut states);
+    for c in path.chars() {
+        next.fill(false);
+        for (i, token) in tokens.iter().enumerate() {
+            if !states[i] {
+                continue;
+            }
+            match *token {
+                Token::Any => next[i] = true,
+                Token::Segment if c != ':' => next[i] = true,
+                Token::Segment => {}
+                Token::One => next[i + 1] = true,
+                Token::Char(p) if p == c => next[i + 1] = true,
+                Token::Char(_) => {}
+            }
+        }
+        close(&mut next);
+        std::mem::swap(&mut states, &mut next);
+        if !states.contains(&true) {
+            return false;
+        }
+    }
+    states[tokens.len()]
+}
+
+/// Produces the number of extant instances of the registered type with the
+/// given [`TypeId`], if any.
+///
+/// See [`Registry::get`](crate::registry::Registry::get) for which type ids
+/// are known.
+///
+/// ```
+/// use std::any::TypeId;
+/// use type_census::{census, registry, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<Foo>();
+///
+/// let _foo = Foo { _instance: Instance::new() };
+/// assert_eq!(census::count_of(TypeId::of::<Foo>()), Some(1));
+/// assert_eq!(census::count_of(TypeId::of::<String>()), None);
+/// ```
+pub fn count_of(type_id: TypeId) -> Option<isize> {
+    registry().get(type_id).map(|entry| entry.count())
+}
+
+/// Cross-references `values` against the global [registry](crate::registry),
+/// producing which registered types appear among them.
+///
+/// This audits heterogeneous containers, such as plugin stores and
+/// grab-bag maps of `Box<dyn Any>`, against the census: for each
+/// registered type among `values`, the [`Audit`] holds how many of the
+/// values are of that type, and how many instances of it exist in total.
+/// Values are matched by [`TypeId`], so a type is recognized only if its
+/// type id is known to the registry; see
+/// [`Registry::get`](crate::registry::Registry::get).
+///
+/// Pass the values themselves, not the boxes holding them: a
+/// `&Box<dyn Any>` is itself a `&dyn Any`, of type `Box<dyn Any>`.
+///
+/// ```
+/// use std::any::Any;
+/// use type_census::{census, registry, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct Plugin {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<Plugin>();
+///
+/// let _elsewhere = Plugin { _instance: Instance::new() };
+/// let store: Vec<Box<dyn Any>> = vec![
+///     Box::new(Plugin { _instance: Instance::new() }),
+///     Box::new(Plugin { _instance: Instance::new() }),
+///     Box::new(String::from("not tabulated")),
+/// ];
+///
+/// let audit = census::audit(store.iter().map(|value| &**value));
+/// let plugin = concat!(module_path!(), "::Plugin");
+/// assert_eq!(audit.iter().collect::<Vec<_>>(), [(plugin, 2, 3)]);
+/// assert_eq!(audit.unregistered(), 1);
+/// ```
+pub fn audit<'a, I>(values: I) -> Audit
+where
+    I: IntoIterator<Item = &'a dyn Any>,
+{
+    let registry = registry();
+    let mut audit = Audit::default();
+    let mut indices: BTreeMap<TypeId, Option<usize>> = BTreeMap::new();
+    for value in values {
+        let type_id = value.type_id();
+        let index = *indices.entry(type_id).or_insert_with(|| {
+            let entry = registry.get(type_id)?;
+            audit.types.push((entry, 0));
+            Some(audit.types.len() - 1)
+        });
+        match index {
+            Some(index) => audit.types[index].1 += 1,
+            None => audit.unregistered += 1,
+        }
+    }
+    audit
+}
+
+/// The registered types among a collection of values, as produced by
+/// [`audit`].
+#[derive(Clone, Debug, Default)]
+pub struct Audit {
+    types: Vec<(Entry, usize)>,
+    unregistered: usize,
+}
+
+impl Audit {
+    /// Produces the number of the values of the type at `name`, and the
+    /// number of extant instances of that type, if any of the values are of
+    /// that type.
+    pub fn get(&self, name: &str) -> Option<(usize, isize)> {
+        self.iter()
+            .find(|(n, ..)| *n == name)
+            .map(|(_, values, count)| (values, count))
+    }
+
+    /// Produces the path of each registered type among the values, the
+    /// number of the values of that type, and the number of extant
+    /// instances of that type, in order of first appearance.
+    ///
+    /// Counts are read as this iterates.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, usize, isize)> + '_ {
+        self.types
+            .iter()
+            .map(|(entry, values)| (entry.name(), *values, entry.count()))
+    }
+
+    /// Produces the number of the values whose types are not registered, or
+    /// are not known by their type id.
+    pub fn unregistered(&self) -> usize {
+        self.unregistered
+    }
+}
+
+/// Zeroes the counter of every type in the global
+/// [registry](crate::registry).
+///
+/// This is meant for harnesses that reuse a process across iterations, such
+/// as persistent-mode fuzzers, and is only available with the `reset`
+/// feature. Instances alive during the reset are still counted down when
+/// they are dropped, so their types' counts go negative; reset only when no
+/// tabulated values are alive. Subscribers are not notified.
+///
+/// ```
+/// use type_census::{census, registry, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<Foo>();
+///
+/// let foo = Foo { _instance: Instance::new() };
+/// std::mem::forget(foo);
+/// assert_eq!(Foo::instances(), 1);
+///
+/// census::reset_all();
+/// assert_eq!(Foo::instances(), 0);
+/// ```
+#[cfg(feature = "reset")]
+pub fn reset_all() {
+    for entry in registry().entries() {
+        entry.reset();
+    }
+}
+
+/// Takes a [`Snapshot`] of the `n` registered types with the most extant
+/// instances, in descending order of count.
+///
+/// Ties are broken by type path. Only `n` types are retained while the
+/// registry is scanned, so this is cheaper than sorting a full [`report`].
+///
+/// ```
+/// use type_census::{census, registry, Instance, Tabulate};
+///
+/// #[derive(Clone, Tabulate)]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// #[derive(Clone, Tabulate)]
+/// pub struct Bar {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<Foo>();
+/// registry().register::<Bar>();
+///
+/// let _foos = vec![Foo { _instance: Instance::new() }; 2];
+/// let _bars = vec![Bar { _instance: Instance::new() }; 5];
+///
+/// let foo = concat!(module_path!(), "::Foo");
+/// let bar = concat!(module_path!(), "::Bar");
+///
+/// let top = census::top_n(1);
+/// assert_eq!(top.iter().collect::<Vec<_>>(), [(bar, 5)]);
+///
+/// let top = census::top_n(10);
+/// assert_eq!(top.iter().collect::<Vec<_>>(), [(bar, 5), (foo, 2)]);
+/// ```
+pub fn top_n(n: usize) -> Snapshot {
+    if n == 0 {
+        return Snapshot::default();
+    }
+    let entries: Vec<_> = registry().entries().collect();
+    let mut top = BinaryHeap::with_capacity(n + 1);
+    for (i, entry) in entries.iter().enumerate() {
+        top.push(Reverse((entry.count(), Reverse(entry.name()), i)));
+        if top.len() > n {
+            top.pop();
+        }
+    }
+    Snapshot::of_entries(
+        top.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((count, _, i))| (entries[i], count)),
+    )
+}
+
+/// Produces a [`Snapshot`] of the registered states of the state machine
+/// `M`, one per state: the per-state breakdown of the machine's population.
+///
+/// A type is a state of `M` if it derives [`State`](crate::State) with
+/// `#[Tabulate(state_of = "M")]`. Only states in the global
+/// [registry](crate::registry) are included.
+///
+/// ```
+/// use type_census::{census, registry, Instance, Tabulate};
+///
+/// pub struct Conn;
+///
+/// #[derive(Clone, Tabulate)]
+/// #[Tabulate(state_of = "Conn")]
+/// pub struct Idle {
+///     _instance: Instance<Self>,
+/// }
+///
+/// #[derive(Clone, Tabulate)]
+/// #[Tabulate(state_of = "Conn")]
+/// pub struct Active {
+///     _instance: Instance<Self>,
+/// }
+///
+/// #[derive(Tabulate)]
+/// pub struct Unrelated {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<Idle>();
+/// registry().register::<Active>();
+/// registry().register::<Unrelated>();
+///
+/// let _idle = vec![Idle { _instance: Instance::new() }; 3];
+/// let _active = vec![Active { _instance: Instance::new() }; 2];
+///
+/// let idle = concat!(module_path!(), "::Idle");
+/// let active = concat!(module_path!(), "::Active");
+/// let states = census::states::<Conn>();
+/// let mut states: Vec<_> = states.iter().collect();
+/// states.sort();
+/// assert_eq!(states, [(active, 2), (idle, 3)]);
+/// ```
+pub fn states<M>() -> Snapshot
+where
+    M: 'static,
+{
+    let machine = std::any::type_name::<M>();
+    Snapshot::of_entries(
+        registry()
+            .filter(|entry| entry.metadata().state_of == Some(machine))
+            .map(|entry| (entry, entry.count())),
+    )
+}
+
+/// The longest interval between two polls of [`block_until_zero`].
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Blocks the current thread until there are no extant instances of `T`, or
+/// until `timeout` elapses.
+///
+/// Produces `true` if the population of `T` reached zero, and `false` if the
+/// timeout elapsed first. This polls [`Tabulate::instances`] with an
+/// exponential backoff, capped at 50ms between polls; it is meant for
+/// "drain then exit" shutdown paths, not for hot loops.
+///
+/// ```
+/// use std::time::Duration;
+/// use type_census::{census, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct Connection {
+///     _instance: Instance<Self>,
+/// }
+///
+/// let connection = Connection { _instance: Instance::new() };
+/// assert!(!census::block_until_zero::<Connection>(Duration::from_millis(1)));
+///
+/// std::thread::spawn(move || drop(connection));
+/// assert!(census::block_until_zero::<Connection>(Duration::from_secs(10)));
+/// ```
+pub fn block_until_zero<T>(timeout: Duration) -> bool
+where
+    T: Tabulate,
+{
+    block_until_zero_with_clock::<T, _>(timeout, SystemClock)
+}
+
+/// Blocks the current thread until there are no extant instances of `T`, or
+/// until `timeout` elapses according to `clock`.
+///
+/// The backoff between polls is slept with [`Clock::sleep`], so with a
+/// [`MockClock`](crate::clock::MockClock), which advances instead of
+/// sleeping, this never blocks. Otherwise, this is like
+/// [`block_until_zero`].
+///
+/// ```
+/// use std::time::Duration;
+/// use type_census::clock::{Clock, MockClock};
+/// use type_census::{census, Instance, Tabulate};
+///
+/// static CLOCK: MockClock = MockClock::new();
+///
+/// #[derive(Tabulate)]
+/// pub struct Connection {
+///     _instance: Instance<Self>,
+/// }
+///
+/// let _connection = Connection { _instance: Instance::new() };
+/// let start = CLOCK.now();
+/// let drained = census::block_until_zero_with_clock::<Connection, _>(Duration::from_secs(60), &CLOCK);
+/// assert!(!drained);
+/// assert_eq!(CLOCK.now() - start, Duration::from_secs(60));
+/// ```
+pub fn block_until_zero_with_clock<T, C>(timeout: Duration, clock: C) -> bool
+where
+    T: Tabulate,
+    C: Clock,
+{
+    let deadline = clock.now() + timeout;
+    let mut interval = Duration::from_millis(1);
+    loop {
+        if T::instances().is_zero() {
+            return true;
+        }
+        let now = clock.now();
+        if now >= deadline {
+            return false;
+        }
+        clock.sleep(interval.min(deadline - now));
+        interval = (interval * 2).min(MAX_POLL_INTERVAL);
+    }
+}
+
+/// A change in the population of a tabulated type, delivered to
+/// [subscribers](subscribe).
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct Event {
+    /// The metadata of the type whose population changed.
+    pub metadata: Metadata,
+    /// The number of extant instances of the type, after the change.
+    pub count: isize,
+    /// The change in the number of extant instances of the type.
+    pub delta: isize,
+}
+
+type Callback = Arc<dyn Fn(&Event) + Send + Sync>;
+
+struct Subscriber {
+    id: u64,
+    granularity: isize,
+    callback: Callback,
+}
+
+static SUBSCRIBED: AtomicBool = AtomicBool::new(false);
+static SUBSCRIBERS: RwLock<Vec<Subscriber>> = RwLock::new(Vec::new());
+
+/// Subscribes `callback` to changes in the population of every tabulated
+/// type.
+///
+/// The callback is invoked, on the thread that made the change, whenever the
+/// population of a type crosses a multiple of `granularity`. It is
+/// unsubscribed when the returned [`Subscription`] is dropped.
+///
+/// While there are no subscribers, constructing and dropping instances
+/// costs one extra relaxed atomic load. Subscribers are never notified when
+/// counting is [disabled](crate#disabling). The callback may subscribe and
+/// unsubscribe, but should avoid constructing or dropping tabulated values.
+///
+/// Subscribers observe exactly which multiples an update crossed if the
+/// type's counter implements [`Counter::fetch_add`] and
+/// [`Counter::fetch_sub`]; otherwise, the population is read after the
+/// update, and concurrent updates may obscure crossings.
+///
+/// ```
+/// use std::num::NonZeroUsize;
+/// use std::sync::{Arc, Mutex};
+/// use type_census::{census, Instance, Tabulate};
+///
+/// #[derive(Clone, Tabulate)]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// let events = Arc::new(Mutex::new(Vec::new()));
+/// let subscription = census::subscribe(NonZeroUsize::new(10).unwrap(), {
+///     let events = events.clone();
+///     move |event| events.lock().unwrap().push((event.metadata.name, event.count))
+/// });
+///
+/// let foos = vec![Foo { _instance: Instance::new() }; 25];
+/// drop(foos);
+/// drop(subscription);
+///
+/// assert_eq!(
+///     *events.lock().unwrap(),
+///     [("Foo", 10), ("Foo", 20), ("Foo", 19), ("Foo", 9)]
+/// );
+/// ```
+pub fn subscribe<F>(granularity: NonZeroUsize, callback: F) -> Subscription
+where
+    F: Fn(&Event) + Send + Sync + 'static,
+{
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let mut subscribers = SUBSCRIBERS.write().unwrap_or_else(PoisonError::into_inner);
+    subscribers.push(Subscriber {
+        id,
+        granularity: isize::try_from(granularity.get()).unwrap_or(isize::MAX),
+        callback: Arc::new(callback),
+    });
+    SUBSCRIBED.store(true, Ordering::Relaxed);
+    Subscription { id }
+}
+
+/// A subscription created by [`subscribe`].
+///
+/// Dropping it unsubscribes its callback.
+#[must_use = "dropping a `Subscription` unsubscribes it"]
+#[derive(Debug)]
+pub struct Subscription {
+    id: u64,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let mut subscribers = SUBSCRIBERS.write().unwrap_or_else(PoisonError::into_inner);
+        subscribers.retain(|subscriber| subscriber.id != self.id);
+        SUBSCRIBED.store(!subscribers.is_empty(), Ordering::Relaxed);
+    }
+}
+
+/// Adds `n`, which is `delta` instances, to the population of `T`, and
+/// notifies subscribers.
+#[inline(always)]
+pub(crate) fn add<T>(n: Primitive<T>, delta: isize)
+where
+    T: Tabulate,
+{
+    let counter = T::counter();
+    if !cfg!(type_census_disabled) && SUBSCRIBED.load(Ordering::Relaxed) {
+        notify::<T>(
+            counter.fetch_add(n).ok().and_then(T::Counter::to_isize),
+            delta,
+        );
+    } else {
+        counter.add_assign(n);
+    }
+}
+
+/// Notifies subscribers that `delta` instances were added to the population
+/// of `T`, which was `before` just beforehand, if known.
+#[inline(always)]
+pub(crate) fn added<T>(before: Option<isize>, delta: isize)
+where
+    T: Tabulate,
+{
+    if !cfg!(type_census_disabled) && SUBSCRIBED.load(Ordering::Relaxed) {
+        notify::<T>(before, delta);
+    }
+}
+
+/// Subtracts `n`, which is `delta` instances, from the population of `T`,
+/// and notifies subscribers and [waiters](crate::limit).
+#[inline(always)]
+pub(crate) fn sub<T>(n: Primitive<T>, delta: isize)
+where
+    T: Tabulate,
+{
+    let counter = T::counter();
+    if !cfg!(type_census_disabled) && SUBSCRIBED.load(Ordering::Relaxed) {
+        let before = counter.fetch_sub(n).ok().and_then(T::Counter::to_isize);
+        notify::<T>(before, delta.wrapping_neg());
+    } else {
+        counter.sub_assign(n);
+    }
+    crate::limit::released::<T>();
+}
+
+/// Notifies subscribers that the population of `T` changed by `delta`, from
+/// `before`.
+///
+/// If the counter of `T` could not produce its value from just before the
+/// change, the population is read after the change instead, which may
+/// include concurrent changes.
+#[cold]
+fn notify<T>(before: Option<isize>, delta: isize)
+where
+    T: Tabulate,
+{
+    let (before, count) = match before {
+        Some(before) => (before, before.wrapping_add(delta)),
+        None => {
+            let count = registry::count::<T>();
+            (count.wrapping_sub(delta), count)
+        }
+    };
+    // Callbacks are invoked outside the lock, so they may subscribe and
+    // unsubscribe.
+    let callbacks: Vec<Callback> = SUBSCRIBERS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .filter(|subscriber| {
+            let granularity = subscriber.granularity;
+            before.div_euclid(granularity) != count.div_euclid(granularity)
+        })
+        .map(|subscriber| subscriber.callback.clone())
+        .collect();
+    if callbacks.is_empty() {
+        return;
+    }
+    let event = Event {
+        metadata: T::metadata(),
+        count,
+        delta,
+    };
+    for callback in callbacks {
+        callback(&event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs() {
+        assert!(glob("a::net::*", "a::net::Connection"));
+        assert!(!glob("a::net::*", "a::net::tcp::Stream"));
+        assert!(glob("a::net::**", "a::net::tcp::Stream"));
+        assert!(!glob("a::net::*", "a::network::Packet"));
+        assert!(glob("**Error", "a::io::IoError"));
+        assert!(glob("a::*::Foo", "a::b::Foo"));
+        assert!(!glob("a::*::Foo", "a::b::c::Foo"));
+        assert!(glob("a::Fo?", "a::Foo"));
+        assert!(!glob("a::Fo?", "a::Fooo"));
+        assert!(glob("a::Foo", "a::Foo"));
+        assert!(!glob("a::Foo", "a::Foo2"));
+        assert!(glob("*", "Foo"));
+        assert!(glob("**", ""));
+        assert!(glob("a::**::Foo", "a::b::c::Foo"));
+        assert!(glob("a::*o*o*", "a::Foo_o"));
+        assert!(!glob("a::*o*o*", "a::b::Foo"));
+        assert!(!glob("?", ""));
+        assert!(glob("***", "a::b"));
+    }
+
+    #[test]
+    fn globs_are_polynomial() {
+        // A backtracking matcher takes exponential time on these.
+        let path = "a".repeat(64);
+        let pattern = "*a".repeat(32) + "b";
+        assert!(!glob(&pattern, &path));
+        let pattern = "**a".repeat(32) + "b";
+        assert!(!glob(&pattern, &path));
+    }
+}
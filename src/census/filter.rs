@@ -0,0 +1,162 @@
+//! Selecting types to report, configured like `RUST_LOG`.
+
+use crate::registry::{self, registry};
+use crate::snapshot::Snapshot;
+
+/// The environment variable read by [`Filter::from_env`].
+pub const ENV: &str = "TYPE_CENSUS";
+
+/// A selection of tabulated types, by module path, parsed from directives
+/// such as `my_app::net=on,*=off`.
+///
+/// Directives are separated by commas. Each is a module path or type path,
+/// followed by `=on` or `=off`; a bare path means `=on`. A path matches
+/// itself and everything within it, by whole path segments, as in
+/// [`Registry::filter_prefix`](crate::registry::Registry::filter_prefix);
+/// a trailing `::*` is ignored, so `my_app::net::*` matches the same types
+/// as `my_app::net`. The path `*` matches every type. Each type is selected
+/// by the directive with the longest matching path (the last, if several
+/// are equally long), or, if none match, is selected.
+///
+/// A filter selects which types are [reported](Filter::report); it does not
+/// stop types from being counted. Counting can be compiled out of a build
+/// entirely, for every type; see [disabling](crate#disabling).
+///
+/// ```
+/// use type_census::census::Filter;
+///
+/// let filter: Filter = "my_app::net::*=on,my_app::net::tls=off,*=off".parse()?;
+/// assert!(filter.selects("my_app::net::Connection"));
+/// assert!(filter.selects("my_app::net::tcp::Stream"));
+/// assert!(!filter.selects("my_app::net::tls::Session"));
+/// assert!(!filter.selects("my_app::Config"));
+/// assert!(!filter.selects("my_app::network::Packet"));
+/// # Ok::<(), type_census::census::ParseFilterError>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Filter {
+    /// The path and selection of each directive.
+    directives: Vec<(String, bool)>,
+}
+
+impl Filter {
+    /// Parses the filter in the [`TYPE_CENSUS`](ENV) environment variable.
+    ///
+    /// If the variable is not set, or is not valid unicode, the filter
+    /// selects every type.
+    ///
+    /// ```no_run
+    /// use type_census::census::Filter;
+    ///
+    /// // e.g., with `TYPE_CENSUS=my_app::net=on,*=off`:
+    /// let filter = Filter::from_env().unwrap_or_default();
+    /// println!("{}", filter.report());
+    /// ```
+    pub fn from_env() -> Result<Self, ParseFilterError> {
+        match std::env::var(ENV) {
+            Ok(directives) => directives.parse(),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Produces `true` if this filter selects the type at `path`.
+    pub fn selects(&self, path: &str) -> bool {
+        self.directives
+            .iter()
+            .filter(|(prefix, _)| prefix == "*" || registry::within(path, prefix))
+            .max_by_key(|(prefix, _)| if prefix == "*" { 0 } else { prefix.len() + 1 })
+            .is_none_or(|&(_, on)| on)
+    }
+
+    /// Takes a [`Snapshot`] of every type in the global
+    /// [registry](crate::registry) that this filter selects.
+    pub fn report(&self) -> Snapshot {
+        Snapshot::of_entries(
+            registry()
+                .filter(|entry| self.selects(entry.name()))
+                .map(|entry| (entry, entry.count())),
+        )
+    }
+}
+
+impl std::str::FromStr for Filter {
+    type Err = ParseFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut directives = Vec::new();
+        for directive in s.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+            let (path, on) = match directive.split_once('=') {
+                Some((path, "on")) => (path, true),
+                Some((path, "off")) => (path, false),
+                Some(_) => return Err(ParseFilterError(directive.to_owned())),
+                None => (directive, true),
+            };
+            let path = path.trim();
+            let path = match path.strip_suffix("::*") {
+                Some(module) => module,
+                None => path,
+            };
+            if path.is_empty() {
+                return Err(ParseFilterError(directive.to_owned()));
+            }
+            directives.push((path.to_owned(), on));
+        }
+        Ok(Self { directives })
+    }
+}
+
+/// The error produced when parsing an invalid [`Filter`] directive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseFilterError(String);
+
+impl std::fmt::Display for ParseFilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid type census filter directive `{}`; expected `path`, `path=on` or `path=off`",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseFilterError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "a::b=on, a , *=off,".parse(),
+            Ok(Filter {
+                directives: vec![
+                    ("a::b".to_owned(), true),
+                    ("a".to_owned(), true),
+                    ("*".to_owned(), false)
+                ]
+            })
+        );
+        assert!("a=maybe".parse::<Filter>().is_err());
+        assert!("=on".parse::<Filter>().is_err());
+        assert_eq!("".parse(), Ok(Filter::default()));
+    }
+
+    #[test]
+    fn selects() {
+        let everything = Filter::default();
+        assert!(everything.selects("a::Foo"));
+
+        let filter: Filter = "*=off,a::b=on".parse().unwrap();
+        assert!(filter.selects("a::b::Foo"));
+        assert!(!filter.selects("a::Foo"));
+
+        let filter: Filter = "a=off,a::b::Foo".parse().unwrap();
+        assert!(filter.selects("a::b::Foo"));
+        assert!(!filter.selects("a::b::Bar"));
+        assert!(filter.selects("c::Foo"));
+    }
+}
@@ -0,0 +1,289 @@
+//! Changes in the census over a span of a program, such as a frame of a game
+//! loop, or a request.
+
+use super::{report, subscribe, Subscription};
+use crate::snapshot::Snapshot;
+use crate::Metadata;
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The metadata of each type, and the number of its instances created and
+/// dropped, by module path, name and parameter.
+type Gross = BTreeMap<(&'static str, &'static str, Option<&'static str>), (Metadata, u64, u64)>;
+
+/// Tracks the changes in the census from the moment it is started.
+struct Tracker {
+    before: Snapshot,
+    gross: Arc<Mutex<Gross>>,
+    _subscription: Subscription,
+}
+
+impl Tracker {
+    fn start() -> Self {
+        let gross = Arc::new(Mutex::new(Gross::new()));
+        // Every change crosses a multiple of one.
+        let subscription = subscribe(NonZeroUsize::MIN, {
+            let gross = gross.clone();
+            move |event| {
+                let mut gross = gross.lock().unwrap_or_else(PoisonError::into_inner);
+                let metadata = event.metadata;
+                let (_, created, dropped) = gross
+                    .entry((metadata.module_path, metadata.name, metadata.param))
+                    .or_insert((metadata, 0, 0));
+                if event.delta > 0 {
+                    *created = created.wrapping_add(event.delta.unsigned_abs() as u64);
+                } else {
+                    *dropped = dropped.wrapping_add(event.delta.unsigned_abs() as u64);
+                }
+            }
+        });
+        Self {
+            before: report(),
+            gross,
+            _subscription: subscription,
+        }
+    }
+
+    /// Produces the changes since this tracker was started.
+    fn changes(&self) -> Changes {
+        let diff = self.before.diff(&report());
+        let gross: BTreeMap<String, (u64, u64)> = self
+            .gross
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .map(|(metadata, created, dropped)| (metadata.path(), (*created, *dropped)))
+            .collect();
+        Changes {
+            changes: diff
+                .iter_counts()
+                .map(|(name, before, after)| {
+                    let (created, dropped) = gross.get(name).copied().unwrap_or_default();
+                    let change = Change {
+                        live: after,
+                        net: after.wrapping_sub(before),
+                        created,
+                        dropped,
+                    };
+                    (name.to_owned(), change)
+                })
+                .collect(),
+        }
+    }
+}
+
+static FRAME: Mutex<Option<Tracker>> = Mutex::new(None);
+
+/// Begins a frame, over which [`end_frame`] reports the changes in the
+/// census of every type in the global [registry](crate::registry).
+///
+/// Beginning a frame ends the current frame, if any, discarding its changes.
+/// Frames are global: changes made on every thread are attributed to the
+/// current frame.
+///
+/// While a frame is in progress, every change in the population of every
+/// tabulated type is tallied under a lock, so frames are meant for debug
+/// overlays and diagnostics, not for always-on monitoring. Instances
+/// created and dropped are not tallied when counting is
+/// [disabled](crate#disabling).
+///
+/// ```
+/// use type_census::{census, registry, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct Particle {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<Particle>();
+/// let particle = concat!(module_path!(), "::Particle");
+///
+/// let mut particles = Vec::new();
+/// for frame in 0..3 {
+///     census::begin_frame();
+///     particles.push(Particle { _instance: Instance::new() });
+///     let spark = Particle { _instance: Instance::new() };
+///     drop(spark);
+///     let changes = census::end_frame().unwrap();
+///
+///     let change = changes.get(particle).unwrap();
+///     assert_eq!(change.live, frame + 1);
+///     assert_eq!(change.net, 1);
+///     assert_eq!(change.created, 2);
+///     assert_eq!(change.dropped, 1);
+/// }
+/// ```
+pub fn begin_frame() {
+    let tracker = Tracker::start();
+    let previous = FRAME
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .replace(tracker);
+    drop(previous);
+}
+
+/// Ends the current frame, producing the changes in the census since it
+/// [began](begin_frame), or `None` if no frame is in progress.
+pub fn end_frame() -> Option<Changes> {
+    let tracker = FRAME
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()?;
+    Some(tracker.changes())
+}
+
+/// Takes a baseline of the census of every type in the global
+/// [registry](crate::registry), producing a guard that reports the changes
+/// since.
+///
+/// Read the changes at any time with [`Baseline::report`], or have them
+/// passed to a callback when the guard is dropped with
+/// [`Baseline::on_drop`]. Baselines are independent of each other and of
+/// [frames](begin_frame), so they may nest and overlap. Like frames, they
+/// attribute changes made on every thread to the baseline, and tally every
+/// change while they are alive.
+///
+/// ```
+/// use type_census::{census, registry, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct Session {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<Session>();
+/// let session = concat!(module_path!(), "::Session");
+///
+/// let mut sessions = Vec::new();
+/// let baseline = census::scoped_baseline();
+/// sessions.push(Session { _instance: Instance::new() });
+/// drop(Session { _instance: Instance::new() });
+///
+/// let change = baseline.report().get(session).unwrap();
+/// assert_eq!((change.net, change.created, change.dropped), (1, 2, 1));
+///
+/// let (sender, receiver) = std::sync::mpsc::channel();
+/// let baseline = census::scoped_baseline().on_drop(move |changes| {
+///     sender.send(changes).unwrap();
+/// });
+/// sessions.clear();
+/// drop(baseline);
+///
+/// let change = receiver.recv().unwrap().get(session).unwrap();
+/// assert_eq!((change.net, change.created, change.dropped), (-1, 0, 1));
+/// ```
+pub fn scoped_baseline() -> Baseline {
+    Baseline {
+        tracker: Tracker::start(),
+        on_drop: None,
+    }
+}
+
+/// A baseline of the census, taken by [`scoped_baseline`].
+#[must_use = "dropping a `Baseline` stops tracking changes"]
+pub struct Baseline {
+    tracker: Tracker,
+    on_drop: Option<Box<dyn FnOnce(Changes) + Send>>,
+}
+
+impl Baseline {
+    /// Produces the changes in the census since this baseline was taken.
+    pub fn report(&self) -> Changes {
+        self.tracker.changes()
+    }
+
+    /// Sets `callback` to be passed the changes in the census since this
+    /// baseline was taken, when this baseline is dropped.
+    ///
+    /// This replaces any previously set callback.
+    pub fn on_drop<F>(mut self, callback: F) -> Self
+    where
+        F: FnOnce(Changes) + Send + 'static,
+    {
+        self.on_drop = Some(Box::new(callback));
+        self
+    }
+}
+
+impl Drop for Baseline {
+    fn drop(&mut self) {
+        if let Some(callback) = self.on_drop.take() {
+            callback(self.tracker.changes());
+        }
+    }
+}
+
+impl std::fmt::Debug for Baseline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Baseline").finish_non_exhaustive()
+    }
+}
+
+/// The changes in the population of a type over a span of a program, as
+/// reported in [`Changes`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Change {
+    /// The number of extant instances of the type at the end of the span.
+    pub live: isize,
+    /// The change in the number of extant instances of the type over the
+    /// span.
+    pub net: isize,
+    /// The number of instances of the type created during the span.
+    pub created: u64,
+    /// The number of instances of the type dropped during the span.
+    pub dropped: u64,
+}
+
+/// The per-type changes in the census over a span of a program, such as a
+/// [frame](begin_frame) or the life of a [baseline](scoped_baseline).
+///
+/// Net changes are read from [snapshots](crate::snapshot::Snapshot::diff)
+/// taken at the ends of the span, and gross changes are tallied as they
+/// happen, so under concurrent updates around the ends of the span, the
+/// two may not agree.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Changes {
+    changes: Vec<(String, Change)>,
+}
+
+impl Changes {
+    /// Produces the changes in the population of the type at `name`, if it
+    /// was registered by the end of the span.
+    pub fn get(&self, name: &str) -> Option<Change> {
+        self.changes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, change)| *change)
+    }
+
+    /// Produces the path of each type, and the changes in its population.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Change)> {
+        self.changes
+            .iter()
+            .map(|(name, change)| (name.as_str(), *change))
+    }
+
+    /// Produces the path of each type whose population changed, and the
+    /// changes in its population.
+    ///
+    /// This includes types whose instances were created and dropped without
+    /// a net change.
+    pub fn changed(&self) -> impl Iterator<Item = (&str, Change)> {
+        self.iter()
+            .filter(|(_, change)| change.net != 0 || change.created != 0 || change.dropped != 0)
+    }
+
+    /// Produces the number of types in these changes.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Produces `true` if these changes contain no types.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
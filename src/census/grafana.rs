@@ -0,0 +1,163 @@
+//! Generating a [Grafana](https://grafana.com) dashboard for the census.
+
+use crate::registry::{registry, Entry};
+use crate::snapshot::push_json_string;
+use std::collections::BTreeMap;
+
+/// The title of panels of types without tags.
+const UNTAGGED: &str = "untagged";
+
+/// Produces the JSON model of a Grafana dashboard of every type in the
+/// global [registry](crate::registry), for import into Grafana.
+///
+/// The dashboard has a panel for each [tag](crate::Metadata::tags), in
+/// order of name, followed by a panel for the untagged types. Each panel
+/// has a gauge for each type in it, querying the Prometheus series
+/// `metric{type="<path>"}`; export the census to Prometheus under `metric`,
+/// labeling each type's count with its path. A type with several tags
+/// appears in each of their panels.
+///
+/// ```
+/// use type_census::census::grafana;
+/// use type_census::{registry, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(tags("net"))]
+/// pub struct Connection {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<Connection>();
+///
+/// let dashboard = grafana::dashboard("type_census_instances");
+/// assert!(dashboard.contains(r#""title":"net""#));
+/// assert!(dashboard.contains(concat!(
+///     r#"type_census_instances{type=\""#,
+///     module_path!(),
+///     r#"::Connection\"}"#,
+/// )));
+/// ```
+pub fn dashboard(metric: &str) -> String {
+    let mut panels: BTreeMap<&str, Vec<Entry>> = BTreeMap::new();
+    let mut untagged = Vec::new();
+    for entry in registry().entries() {
+        if entry.metadata().tags.is_empty() {
+            untagged.push(entry);
+        }
+        for &tag in entry.metadata().tags {
+            panels.entry(tag).or_default().push(entry);
+        }
+    }
+    let panels = panels
+        .into_iter()
+        .chain((!untagged.is_empty()).then_some((UNTAGGED, untagged)));
+    let mut json = String::from(r#"{"title":"Type census","schemaVersion":39,"#);
+    json.push_str(r#""time":{"from":"now-1h","to":"now"},"refresh":"30s","panels":["#);
+    for (i, (title, entries)) in panels.enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str(&format!(r#"{{"id":{},"type":"gauge","title":"#, i + 1));
+        push_json_string(&mut json, title);
+        json.push_str(&format!(
+            r#","gridPos":{{"h":8,"w":12,"x":{},"y":{}}},"targets":["#,
+            i % 2 * 12,
+            i / 2 * 8
+        ));
+        for (j, entry) in entries.iter().enumerate() {
+            if j > 0 {
+                json.push(',');
+            }
+            json.push_str(r#"{"refId":"#);
+            push_json_string(&mut json, &ref_id(j));
+            json.push_str(r#","expr":"#);
+            push_json_string(
+                &mut json,
+                &format!(r#"{metric}{{type="{}"}}"#, promql_string(entry.name())),
+            );
+            json.push_str(r#","legendFormat":"#);
+            push_json_string(&mut json, entry.metadata().name);
+            json.push('}');
+        }
+        json.push_str("]}");
+    }
+    json.push_str("]}");
+    json
+}
+
+/// Produces the `i`th query id of a panel: `A` to `Z`, then `AA`, `AB`,
+/// and so on.
+fn ref_id(mut i: usize) -> String {
+    let mut id = Vec::new();
+    loop {
+        id.push(b'A' + (i % 26) as u8);
+        i /= 26;
+        if i == 0 {
+            break;
+        }
+        i -= 1;
+    }
+    id.reverse();
+    String::from_utf8(id).unwrap_or_default()
+}
+
+/// Escapes `s` for a double-quoted PromQL string, or a label value in the
+/// Prometheus text exposition format.
+pub(crate) fn promql_string(s: &str) -> String {
+    s.replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ref_ids() {
+        let ids: Vec<_> = [0, 1, 25, 26, 27, 51, 52, 701, 702]
+            .into_iter()
+            .map(ref_id)
+            .collect();
+        assert_eq!(ids, ["A", "B", "Z", "AA", "AB", "AZ", "BA", "ZZ", "AAA"]);
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(promql_string(r#"a::Foo"\"#), r#"a::Foo\"\\"#);
+    }
+
+    struct Foo;
+
+    impl crate::Tabulate for Foo {
+        type Counter = crate::counter::RelaxedCounter;
+
+        fn counter() -> &'static Self::Counter {
+            static COUNTER: crate::counter::RelaxedCounter = crate::counter::RelaxedCounter::new();
+            &COUNTER
+        }
+
+        fn metadata() -> crate::Metadata {
+            crate::Metadata::of::<Self>().tagged(&["grafana", "tests"])
+        }
+    }
+
+    #[test]
+    fn valid_json() {
+        registry().register::<Foo>();
+        let dashboard: serde_json::Value = serde_json::from_str(&dashboard("m")).unwrap();
+        let panels = dashboard["panels"].as_array().unwrap();
+        for (i, panel) in panels.iter().enumerate() {
+            assert_eq!(panel["id"], i + 1);
+            assert_eq!(panel["type"], "gauge");
+        }
+        for tag in ["grafana", "tests"] {
+            let panel = panels.iter().find(|panel| panel["title"] == tag).unwrap();
+            assert_eq!(
+                panel["targets"][0]["expr"],
+                r#"m{type="type_census::census::grafana::tests::Foo"}"#
+            );
+            assert_eq!(panel["targets"][0]["legendFormat"], "Foo");
+        }
+    }
+}
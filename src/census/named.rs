@@ -0,0 +1,127 @@
+//! Counters keyed by runtime strings, for populations that aren't distinct
+//! Rust types.
+
+use crate::counter::{Counter, RelaxedCounter};
+use crate::snapshot::Snapshot;
+use std::collections::BTreeMap;
+use std::sync::{PoisonError, RwLock};
+
+static COUNTERS: RwLock<BTreeMap<&'static str, &'static RelaxedCounter>> =
+    RwLock::new(BTreeMap::new());
+
+/// Produces the counter named `name`, creating it if it doesn't exist.
+fn named(name: &str) -> (&'static str, &'static RelaxedCounter) {
+    let counters = COUNTERS.read().unwrap_or_else(PoisonError::into_inner);
+    if let Some((name, counter)) = counters.get_key_value(name) {
+        return (name, counter);
+    }
+    drop(counters);
+    let mut counters = COUNTERS.write().unwrap_or_else(PoisonError::into_inner);
+    if let Some((name, counter)) = counters.get_key_value(name) {
+        return (name, counter);
+    }
+    let name: &'static str = Box::leak(name.to_owned().into_boxed_str());
+    let counter: &'static RelaxedCounter = Box::leak(Box::new(RelaxedCounter::ZERO));
+    counters.insert(name, counter);
+    (name, counter)
+}
+
+/// Constructs a new [`NamedInstance`], incrementing the counter named
+/// `name`.
+///
+/// Counters are created on first use, and never freed. Looking a counter up
+/// by name takes a lock; to count many instances under one name, construct
+/// one `NamedInstance` and [clone](Clone::clone) it.
+///
+/// ```
+/// use type_census::census;
+///
+/// let socket = census::counter("open_websockets");
+/// let another = socket.clone();
+/// assert_eq!(census::named_count("open_websockets"), Some(2));
+///
+/// drop((socket, another));
+/// assert_eq!(census::named_count("open_websockets"), Some(0));
+/// assert_eq!(census::named_count("open_files"), None);
+/// ```
+pub fn counter(name: &str) -> NamedInstance {
+    let (name, counter) = named(name);
+    counter.add_assign(1);
+    NamedInstance { name, counter }
+}
+
+/// Produces the current value of the counter named `name`, if it exists.
+pub fn named_count(name: &str) -> Option<isize> {
+    COUNTERS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(name)
+        .map(|counter| counter.fetch())
+}
+
+/// Takes a [`Snapshot`] of every named counter, in order of name.
+pub fn named_report() -> Snapshot {
+    COUNTERS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .map(|(name, counter)| (*name, counter.fetch()))
+        .collect()
+}
+
+/// A guard that tracks the lifetime of one member of a population named by
+/// a runtime string.
+///
+/// Construct one with [`counter`]. Dropping it decrements the counter.
+pub struct NamedInstance {
+    name: &'static str,
+    counter: &'static RelaxedCounter,
+}
+
+impl NamedInstance {
+    /// Produces the name of the counter tracking this instance.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl std::fmt::Debug for NamedInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NamedInstance")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl Clone for NamedInstance {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        self.counter.add_assign(1);
+        Self {
+            name: self.name,
+            counter: self.counter,
+        }
+    }
+}
+
+impl Drop for NamedInstance {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.counter.sub_assign(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report() {
+        let _a = counter("type_census::named::tests::a");
+        let _b = counter("type_census::named::tests::b");
+        let _b2 = counter("type_census::named::tests::b");
+        let report = named_report();
+        assert_eq!(report.get("type_census::named::tests::a"), Some(1));
+        assert_eq!(report.get("type_census::named::tests::b"), Some(2));
+    }
+}
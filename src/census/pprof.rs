@@ -0,0 +1,305 @@
+//! Exporting the census as a [pprof](https://github.com/google/pprof)
+//! profile.
+//!
+//! [`profile`] encodes the population of every registered type as a pprof
+//! `Profile` protocol buffer, so it can be explored with `go tool pprof`,
+//! speedscope, or any other viewer of pprof profiles:
+//! ```text
+//! go tool pprof -http=:8080 census.pb
+//! ```
+//! Each type is a sample whose stack is its module path, so flame graphs
+//! group types by crate and module. Every sample carries two values: the
+//! number of extant instances of the type (`instances/count`), and the
+//! memory they occupy (`bytes/bytes`). If the type's counter
+//! [tracks bytes](crate::counter::Counter::fetch_bytes), its byte total is
+//! used; otherwise, the memory is counted by the type's
+//! [size](crate::Metadata::size), and memory the instances own indirectly,
+//! such as the buffer of a `Vec`, is not included.
+//!
+//! With the `creation-sites` feature, the profile carries a third value,
+//! `created/count`: the number of instances of each type created at each of
+//! its [creation sites](crate::sites), as a sample whose stack is the site,
+//! named by its source location, above the type. Sites count creations, not
+//! survivors, so these samples have no instances or bytes, and the types'
+//! samples have no creations.
+//!
+//! The profile is not compressed; the pprof tools accept it as is, or
+//! gzipped.
+
+use crate::registry::registry;
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+/// Encodes the census of every type in the global
+/// [registry](crate::registry) as a pprof profile.
+///
+/// ```
+/// use type_census::census::pprof;
+/// use type_census::{registry, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<Foo>();
+/// let _foo = Foo { _instance: Instance::new() };
+///
+/// let profile = pprof::profile();
+/// assert!(!profile.is_empty());
+/// // e.g., std::fs::write("census.pb", profile)
+/// ```
+pub fn profile() -> Vec<u8> {
+    let mut profile = Profile::default();
+    let instances = profile.string("instances");
+    let count = profile.string("count");
+    let bytes = profile.string("bytes");
+    let mut sample_types = vec![(instances, count), (bytes, bytes)];
+    if cfg!(feature = "creation-sites") {
+        let created = profile.string("created");
+        sample_types.push((created, count));
+    }
+    for (ty, unit) in sample_types {
+        let mut value_type = Vec::new();
+        int(&mut value_type, 1, ty);
+        int(&mut value_type, 2, unit);
+        message(&mut profile.buf, 1, &value_type);
+    }
+    for entry in registry().entries() {
+        let name = entry.name();
+        let count = entry.count();
+        let mut stack = vec![profile.location(name, "", 0)];
+        let mut path = name;
+        while let Some((module, _)) = path.rsplit_once("::") {
+            stack.push(profile.location(module, "", 0));
+            path = module;
+        }
+        let bytes = match entry.bytes() {
+            Some(bytes) => bytes as isize,
+            None => count.wrapping_mul(entry.metadata().size as isize),
+        };
+        let mut values = vec![count, bytes];
+        if cfg!(feature = "creation-sites") {
+            values.push(0);
+        }
+        profile.sample(&stack, &values);
+        #[cfg(feature = "creation-sites")]
+        for (location, created) in crate::sites::by_counter(entry.counter()) {
+            let site = profile.location(
+                &location.to_string(),
+                location.file(),
+                location.line().into(),
+            );
+            stack.insert(0, site);
+            profile.sample(&stack, &[0, 0, created as isize]);
+            stack.remove(0);
+        }
+    }
+    profile.finish()
+}
+
+/// A pprof profile under construction.
+#[derive(Default)]
+struct Profile {
+    /// The encoded fields of the profile, other than its functions,
+    /// locations and strings.
+    buf: Vec<u8>,
+    /// The index of each string in the string table.
+    strings: BTreeMap<String, usize>,
+    /// The id of each function, by name and file name.
+    functions: BTreeMap<(usize, usize), u64>,
+    /// The id of each location, by function id and line.
+    locations: BTreeMap<(u64, i64), u64>,
+}
+
+impl Profile {
+    /// Produces the index of `s` in the string table, adding it if it's
+    /// new.
+    fn string(&mut self, s: &str) -> i64 {
+        if self.strings.is_empty() {
+            // The first string must be empty.
+            self.strings.insert(String::new(), 0);
+        }
+        let next = self.strings.len();
+        *self.strings.entry(s.to_owned()).or_insert(next) as i64
+    }
+
+    /// Produces the id of the location of `line` of `file`, in the function
+    /// `name`, adding it if it's new.
+    fn location(&mut self, name: &str, file: &str, line: i64) -> u64 {
+        let key = (self.string(name) as usize, self.string(file) as usize);
+        let next = self.functions.len() as u64 + 1;
+        let function = *self.functions.entry(key).or_insert(next);
+        let next = self.locations.len() as u64 + 1;
+        *self.locations.entry((function, line)).or_insert(next)
+    }
+
+    /// Appends a sample with the given stack, leaf first, and values.
+    fn sample(&mut self, stack: &[u64], values: &[isize]) {
+        let mut sample = Vec::new();
+        packed(&mut sample, 1, stack.iter().copied());
+        packed(&mut sample, 2, values.iter().map(|&value| value as u64));
+        message(&mut self.buf, 2, &sample);
+    }
+
+    /// Appends the locations, functions and string table, and the time,
+    /// producing the encoded profile.
+    fn finish(self) -> Vec<u8> {
+        let mut buf = self.buf;
+        for (&(function, line), &id) in &self.locations {
+            let mut location = Vec::new();
+            uint(&mut location, 1, id);
+            let mut line_message = Vec::new();
+            uint(&mut line_message, 1, function);
+            int(&mut line_message, 2, line);
+            message(&mut location, 4, &line_message);
+            message(&mut buf, 4, &location);
+        }
+        for (&(name, file), &id) in &self.functions {
+            let mut function = Vec::new();
+            uint(&mut function, 1, id);
+            int(&mut function, 2, name as i64);
+            int(&mut function, 3, name as i64);
+            int(&mut function, 4, file as i64);
+            message(&mut buf, 5, &function);
+        }
+        let mut strings: Vec<_> = self.strings.into_iter().collect();
+        strings.sort_by_key(|&(_, index)| index);
+        for (s, _) in strings {
+            message(&mut buf, 6, s.as_bytes());
+        }
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |now| now.as_nanos() as i64);
+        int(&mut buf, 9, now);
+        buf
+    }
+}
+
+/// The wire type of varint fields.
+const VARINT: u64 = 0;
+
+/// The wire type of length-delimited fields.
+const LEN: u64 = 2;
+
+/// Appends `value` as a base-128 varint.
+fn varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// Appends an unsigned integer field.
+fn uint(buf: &mut Vec<u8>, field: u64, value: u64) {
+    varint(buf, field << 3 | VARINT);
+    varint(buf, value);
+}
+
+/// Appends a signed integer field.
+fn int(buf: &mut Vec<u8>, field: u64, value: i64) {
+    uint(buf, field, value as u64);
+}
+
+/// Appends a length-delimited field: an embedded message or a string.
+fn message(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    varint(buf, field << 3 | LEN);
+    varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+/// Appends a packed repeated integer field.
+fn packed(buf: &mut Vec<u8>, field: u64, values: impl Iterator<Item = u64>) {
+    let mut bytes = Vec::new();
+    for value in values {
+        varint(&mut bytes, value);
+    }
+    message(buf, field, &bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes the fields of a message, as field numbers and either varints
+    /// or bytes.
+    fn decode(mut buf: &[u8]) -> Vec<(u64, Result<u64, &[u8]>)> {
+        fn varint(buf: &mut &[u8]) -> u64 {
+            let mut value = 0;
+            for shift in (0..).step_by(7) {
+                let byte = buf[0];
+                *buf = &buf[1..];
+                value |= u64::from(byte & 0x7f) << shift;
+                if byte < 0x80 {
+                    break;
+                }
+            }
+            value
+        }
+        let mut fields = Vec::new();
+        while !buf.is_empty() {
+            let key = varint(&mut buf);
+            let value = match key & 7 {
+                VARINT => Ok(varint(&mut buf)),
+                _ => {
+                    let len = varint(&mut buf) as usize;
+                    let (bytes, rest) = buf.split_at(len);
+                    buf = rest;
+                    Err(bytes)
+                }
+            };
+            fields.push((key >> 3, value));
+        }
+        fields
+    }
+
+    #[test]
+    fn varints() {
+        let mut buf = Vec::new();
+        varint(&mut buf, 1);
+        varint(&mut buf, 300);
+        varint(&mut buf, -1i64 as u64);
+        assert_eq!(buf[..3], [0x01, 0xac, 0x02]);
+        assert_eq!(buf.len(), 3 + 10);
+    }
+
+    #[test]
+    fn encode() {
+        let mut profile = Profile::default();
+        let instances = profile.string("instances");
+        let count = profile.string("count");
+        let type_location = profile.location("a::Foo", "", 0);
+        let module_location = profile.location("a", "", 0);
+        assert_eq!(profile.location("a::Foo", "", 0), type_location);
+        profile.sample(&[type_location, module_location], &[3]);
+        let mut value_type = Vec::new();
+        int(&mut value_type, 1, instances);
+        int(&mut value_type, 2, count);
+        message(&mut profile.buf, 1, &value_type);
+        let buf = profile.finish();
+
+        let fields = decode(&buf);
+        let strings: Vec<_> = fields
+            .iter()
+            .filter(|(field, _)| *field == 6)
+            .map(|(_, value)| std::str::from_utf8(value.unwrap_err()).unwrap())
+            .collect();
+        assert_eq!(strings, ["", "instances", "count", "a::Foo", "a"]);
+
+        let samples: Vec<_> = fields
+            .iter()
+            .filter(|(field, _)| *field == 2)
+            .map(|(_, value)| decode(value.unwrap_err()))
+            .collect();
+        assert_eq!(
+            samples,
+            [vec![(1, Err(&[1u8, 2][..])), (2, Err(&[3u8][..]))]]
+        );
+
+        let functions = fields.iter().filter(|(field, _)| *field == 5).count();
+        let locations = fields.iter().filter(|(field, _)| *field == 4).count();
+        assert_eq!((functions, locations), (2, 2));
+        assert!(matches!(fields.last(), Some((9, Ok(_)))));
+    }
+}
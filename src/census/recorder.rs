@@ -0,0 +1,368 @@
+//! Recording the census over time.
+
+use crate::census::report;
+use crate::clock::{Clock, SystemClock};
+use crate::snapshot::Snapshot;
+use std::io::{self, Write};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Starts sampling the global census on a background thread, once every
+/// `interval`, into an in-memory history.
+///
+/// The first sample is taken before this returns. The thread stops when the
+/// returned [`Recording`] is dropped. The history grows with every sample;
+/// [drain](Recording::drain) it periodically for long recordings. Samples are
+/// timestamped by the system clock; see [`start_with_clock`].
+///
+/// ```
+/// use std::time::Duration;
+/// use type_census::census::recorder;
+/// use type_census::{registry, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<Foo>();
+/// let _foo = Foo { _instance: Instance::new() };
+///
+/// let recording = recorder::start(Duration::from_millis(1));
+/// std::thread::sleep(Duration::from_millis(10));
+/// let series = recording.series(concat!(module_path!(), "::Foo"));
+/// assert!(!series.is_empty());
+/// assert!(series.iter().all(|&(_, count)| count == 1));
+/// ```
+pub fn start(interval: Duration) -> Recording {
+    start_with_clock(interval, SystemClock)
+}
+
+/// Starts sampling the global census on a background thread, once every
+/// `interval`, into an in-memory history, timestamping samples by `clock`.
+///
+/// The interval between samples is still measured in real time; only their
+/// timestamps are read from `clock`. Otherwise, this is like [`start`].
+///
+/// ```
+/// use std::time::Duration;
+/// use type_census::census::recorder;
+/// use type_census::clock::{Clock, MockClock};
+///
+/// static CLOCK: MockClock = MockClock::new();
+///
+/// let recording = recorder::start_with_clock(Duration::from_secs(3600), &CLOCK);
+/// let history = recording.history();
+/// assert_eq!(history.len(), 1);
+/// assert_eq!(history[0].0, CLOCK.now());
+/// ```
+pub fn start_with_clock<C>(interval: Duration, clock: C) -> Recording
+where
+    C: Clock,
+{
+    let history = Arc::new(Mutex::new(vec![(clock.now(), report())]));
+    let (stop, stopped) = mpsc::channel::<()>();
+    let thread = std::thread::Builder::new()
+        .name("type-census-recorder".into())
+        .spawn({
+            let history = history.clone();
+            move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    let sample = (clock.now(), report());
+                    history
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push(sample);
+                }
+            }
+        })
+        .expect("failed to spawn the census recorder thread");
+    Recording {
+        history,
+        stop: Some(stop),
+        thread: Some(thread),
+    }
+}
+
+/// A background recording of the census, started by [`start`].
+///
+/// Dropping it stops the recording thread.
+#[must_use = "dropping a `Recording` stops it"]
+pub struct Recording {
+    history: Arc<Mutex<Vec<(Instant, Snapshot)>>>,
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Recording {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<(Instant, Snapshot)>> {
+        self.history.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Produces a copy of every sample recorded so far, oldest first.
+    pub fn history(&self) -> Vec<(Instant, Snapshot)> {
+        self.lock().clone()
+    }
+
+    /// Removes and produces every sample recorded so far, oldest first.
+    pub fn drain(&self) -> Vec<(Instant, Snapshot)> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    /// Produces the count of the type at path `name` in each sample
+    /// recorded so far, oldest first.
+    ///
+    /// Samples in which the type does not appear are skipped.
+    pub fn series(&self, name: &str) -> Vec<(Instant, isize)> {
+        self.lock()
+            .iter()
+            .filter_map(|(instant, snapshot)| Some((*instant, snapshot.get(name)?)))
+            .collect()
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Appends one CSV row per sample of the census to a writer.
+///
+/// The first sample writes a header row: `elapsed_secs`, followed by the
+/// path of each type in the sample. Every row then holds the seconds
+/// elapsed since the recorder was constructed, followed by the count of
+/// each of those types. Types that appear after the header is written are
+/// not recorded.
+///
+/// ```
+/// use std::time::Duration;
+/// use type_census::census::recorder::CsvRecorder;
+/// use type_census::clock::MockClock;
+/// use type_census::snapshot::Snapshot;
+///
+/// static CLOCK: MockClock = MockClock::new();
+///
+/// let mut recorder = CsvRecorder::with_clock(Vec::new(), &CLOCK);
+/// let sample: Snapshot = [("app::Foo", 1), ("app::Bar", 2)].into_iter().collect();
+/// recorder.record_snapshot(&sample)?;
+/// CLOCK.advance(Duration::from_millis(1500));
+/// let sample: Snapshot = [("app::Foo", 3), ("app::Bar", 0)].into_iter().collect();
+/// recorder.record_snapshot(&sample)?;
+///
+/// assert_eq!(
+///     String::from_utf8(recorder.into_inner()).unwrap(),
+///     "elapsed_secs,app::Foo,app::Bar\n0.000,1,2\n1.500,3,0\n"
+/// );
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct CsvRecorder<W, C = SystemClock>
+where
+    W: Write,
+    C: Clock,
+{
+    writer: W,
+    clock: C,
+    start: Instant,
+    columns: Option<Vec<String>>,
+}
+
+impl<W> CsvRecorder<W>
+where
+    W: Write,
+{
+    /// Constructs a new `CsvRecorder` writing to `writer`, timed by the
+    /// system clock.
+    pub fn new(writer: W) -> Self {
+        Self::with_clock(writer, SystemClock)
+    }
+}
+
+impl<W, C> CsvRecorder<W, C>
+where
+    W: Write,
+    C: Clock,
+{
+    /// Constructs a new `CsvRecorder` writing to `writer`, timed by `clock`.
+    pub fn with_clock(writer: W, clock: C) -> Self {
+        let start = clock.now();
+        Self {
+            writer,
+            clock,
+            start,
+            columns: None,
+        }
+    }
+
+    /// Samples the global census with [`report`], and appends it as a row.
+    pub fn record(&mut self) -> io::Result<()> {
+        self.record_snapshot(&report())
+    }
+
+    /// Appends `snapshot` as a row.
+    pub fn record_snapshot(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        let elapsed = self.clock.now().saturating_duration_since(self.start);
+        let columns = match &self.columns {
+            Some(columns) => columns,
+            None => {
+                let columns: Vec<String> =
+                    snapshot.iter().map(|(name, _)| name.to_owned()).collect();
+                write!(self.writer, "elapsed_secs")?;
+                for name in &columns {
+                    write!(self.writer, ",{}", csv_field(name))?;
+                }
+                writeln!(self.writer)?;
+                self.columns.insert(columns)
+            }
+        };
+        write!(self.writer, "{:.3}", elapsed.as_secs_f64())?;
+        for name in columns {
+            write!(self.writer, ",{}", snapshot.get(name).unwrap_or(0))?;
+        }
+        writeln!(self.writer)?;
+        self.writer.flush()
+    }
+
+    /// Consumes this recorder, producing the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Appends one JSON object per sample of the census to a writer, one per
+/// line.
+///
+/// Each line is a snapshot in the format of [`Snapshot::to_json`], with an
+/// additional `elapsed_secs` field: the seconds elapsed since the recorder
+/// was constructed. Readers of the snapshot [schema](crate::snapshot#schema)
+/// ignore the additional field, so each line can be read back as a
+/// [`Snapshot`] on its own.
+///
+/// ```
+/// use std::time::Duration;
+/// use type_census::census::recorder::JsonLinesRecorder;
+/// use type_census::clock::MockClock;
+/// use type_census::snapshot::Snapshot;
+///
+/// static CLOCK: MockClock = MockClock::new();
+///
+/// let mut recorder = JsonLinesRecorder::with_clock(Vec::new(), &CLOCK);
+/// let sample: Snapshot = [("app::Foo", 1)].into_iter().collect();
+/// recorder.record_snapshot(&sample)?;
+/// CLOCK.advance(Duration::from_millis(1500));
+/// let sample: Snapshot = [("app::Foo", 3), ("app::Bar", 2)].into_iter().collect();
+/// recorder.record_snapshot(&sample)?;
+///
+/// assert_eq!(
+///     String::from_utf8(recorder.into_inner()).unwrap(),
+///     concat!(
+///         r#"{"elapsed_secs":0.000,"schema_version":1,"types":[{"name":"app::Foo","count":1}]}"#,
+///         "\n",
+///         r#"{"elapsed_secs":1.500,"schema_version":1,"types":"#,
+///         r#"[{"name":"app::Foo","count":3},{"name":"app::Bar","count":2}]}"#,
+///         "\n",
+///     )
+/// );
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct JsonLinesRecorder<W, C = SystemClock>
+where
+    W: Write,
+    C: Clock,
+{
+    writer: W,
+    clock: C,
+    start: Instant,
+}
+
+impl<W> JsonLinesRecorder<W>
+where
+    W: Write,
+{
+    /// Constructs a new `JsonLinesRecorder` writing to `writer`, timed by
+    /// the system clock.
+    pub fn new(writer: W) -> Self {
+        Self::with_clock(writer, SystemClock)
+    }
+}
+
+impl<W, C> JsonLinesRecorder<W, C>
+where
+    W: Write,
+    C: Clock,
+{
+    /// Constructs a new `JsonLinesRecorder` writing to `writer`, timed by
+    /// `clock`.
+    pub fn with_clock(writer: W, clock: C) -> Self {
+        let start = clock.now();
+        Self {
+            writer,
+            clock,
+            start,
+        }
+    }
+
+    /// Samples the global census with [`report`], and appends it as a line.
+    pub fn record(&mut self) -> io::Result<()> {
+        self.record_snapshot(&report())
+    }
+
+    /// Appends `snapshot` as a line.
+    pub fn record_snapshot(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        let elapsed = self.clock.now().saturating_duration_since(self.start);
+        let json = snapshot.to_json();
+        // `to_json` produces an object; prepend a field to it.
+        let fields = json.strip_prefix('{').unwrap_or(&json);
+        writeln!(
+            self.writer,
+            r#"{{"elapsed_secs":{:.3},{fields}"#,
+            elapsed.as_secs_f64()
+        )?;
+        self.writer.flush()
+    }
+
+    /// Consumes this recorder, producing the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Quotes `field` if it contains a character special to CSV.
+fn csv_field(field: &str) -> std::borrow::Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_lines_are_snapshots() {
+        let mut recorder = JsonLinesRecorder::new(Vec::new());
+        let sample: Snapshot = [("a::Foo", 1), ("a::Bar", -2)].into_iter().collect();
+        recorder.record_snapshot(&sample).unwrap();
+        recorder.record_snapshot(&sample).unwrap();
+        let lines = String::from_utf8(recorder.into_inner()).unwrap();
+        for line in lines.lines() {
+            assert_eq!(serde_json::from_str::<Snapshot>(line).unwrap(), sample);
+        }
+        assert_eq!(lines.lines().count(), 2);
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(csv_field("a::Foo"), "a::Foo");
+        assert_eq!(csv_field("a::Foo<u8, i8>"), "\"a::Foo<u8, i8>\"");
+        assert_eq!(csv_field("\"quoted\""), "\"\"\"quoted\"\"\"");
+    }
+}
@@ -0,0 +1,225 @@
+//! Querying the census of a live process over a Unix domain socket.
+//!
+//! [`listen`] serves a line-based protocol on a socket, so sidecars and
+//! debugging tools can query a process without HTTP or signals. Each
+//! request is one line, answered by one line:
+//!
+//! - `GET census` is answered with a [`Snapshot`] of every type in the
+//!   global [registry](crate::registry), in the format of
+//!   [`Snapshot::to_json`].
+//! - `GET type <path>` is answered with a snapshot of only the registered
+//!   type at `<path>`, in the same format.
+//!
+//! Any other request, or a request for an unregistered type, is answered
+//! with `ERR`, a space, and a description of the error. For example:
+//! ```text
+//! $ echo 'GET type my_app::net::Connection' | socat - UNIX-CONNECT:/run/my_app/census.sock
+//! {"schema_version":1,"types":[{"name":"my_app::net::Connection","count":12,...}]}
+//! ```
+
+use crate::census::report;
+use crate::registry::registry;
+use crate::snapshot::Snapshot;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// The longest request line, in bytes, including its newline.
+const MAX_REQUEST: usize = 4096;
+
+/// The longest a connection may wait to read a request or write a response
+/// before it is closed.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Binds a Unix domain socket at `path`, and answers queries of the census
+/// on it from a background thread.
+///
+/// The socket is removed, and the thread stopped, when the returned
+/// [`Listener`] is dropped. Connections are served one at a time, each until
+/// the client closes it, or fails to send a request or read a response
+/// within five seconds.
+///
+/// ```
+/// use std::io::{BufRead, BufReader, Write};
+/// use std::os::unix::net::UnixStream;
+/// use type_census::census::socket;
+/// use type_census::{registry, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct Connection {
+///     _instance: Instance<Self>,
+/// }
+///
+/// registry().register::<Connection>();
+/// let _connection = Connection { _instance: Instance::new() };
+///
+/// let path = std::env::temp_dir().join(format!("census-{}.sock", std::process::id()));
+/// let listener = socket::listen(&path)?;
+///
+/// let mut stream = UnixStream::connect(listener.path())?;
+/// writeln!(stream, "GET type {}::Connection", module_path!())?;
+/// let mut response = String::new();
+/// BufReader::new(&stream).read_line(&mut response)?;
+/// assert!(response.contains(r#""count":1"#));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn listen(path: impl AsRef<Path>) -> io::Result<Listener> {
+    let path = path.as_ref().to_owned();
+    let listener = UnixListener::bind(&path)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread = std::thread::Builder::new()
+        .name("type-census-socket".into())
+        .spawn({
+            let stop = stop.clone();
+            move || {
+                for stream in listener.incoming() {
+                    if stop.load(Ordering::Acquire) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        let _ = serve(stream);
+                    }
+                }
+            }
+        })?;
+    Ok(Listener {
+        path,
+        stop,
+        thread: Some(thread),
+    })
+}
+
+/// A socket answering queries of the census, bound by [`listen`].
+///
+/// Dropping it stops answering queries, and removes the socket.
+#[must_use = "dropping a `Listener` stops it"]
+#[derive(Debug)]
+pub struct Listener {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Listener {
+    /// Produces the path of the socket.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        // Wake the thread from `accept`; if the socket can't be reached, the
+        // thread can't be woken, and is left to block.
+        if UnixStream::connect(&self.path).is_ok() {
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Answers each request on `stream` until it is closed.
+fn serve(stream: UnixStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut writer = &stream;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let len = (&mut reader)
+            .take(MAX_REQUEST as u64)
+            .read_line(&mut line)?;
+        if len == 0 {
+            return Ok(());
+        }
+        if len == MAX_REQUEST && !line.ends_with('\n') {
+            writeln!(writer, "ERR request longer than {MAX_REQUEST} bytes")?;
+            return Ok(());
+        }
+        writeln!(writer, "{}", respond(line.trim()))?;
+    }
+}
+
+/// Produces the response to `request`.
+fn respond(request: &str) -> String {
+    if request == "GET census" {
+        return report().to_json();
+    }
+    match request.strip_prefix("GET type ").map(str::trim) {
+        Some(name) => match registry().entries().find(|entry| entry.name() == name) {
+            Some(entry) => Snapshot::of_entries([(entry, entry.count())]).to_json(),
+            None => format!("ERR unknown type `{name}`"),
+        },
+        None => {
+            format!("ERR unknown request `{request}`; expected `GET census` or `GET type <path>`")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Foo;
+
+    impl crate::Tabulate for Foo {
+        type Counter = crate::counter::RelaxedCounter;
+
+        fn counter() -> &'static Self::Counter {
+            static COUNTER: crate::counter::RelaxedCounter = crate::counter::RelaxedCounter::new();
+            &COUNTER
+        }
+    }
+
+    #[test]
+    fn requests() {
+        registry().register::<Foo>();
+        let path = std::env::temp_dir().join(format!(
+            "type-census-socket-tests-{}.sock",
+            std::process::id()
+        ));
+        let listener = listen(&path).unwrap();
+
+        let stream = UnixStream::connect(listener.path()).unwrap();
+        (&stream)
+            .write_all(b"GET census\nGET type type_census::census::socket::tests::Foo\nGET type a::Bar\nPUT x\n")
+            .unwrap();
+        stream.shutdown(std::net::Shutdown::Write).unwrap();
+        let lines: Vec<String> = BufReader::new(&stream)
+            .lines()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains(r#""name":"type_census::census::socket::tests::Foo""#));
+        assert!(lines[1].starts_with(
+            r#"{"schema_version":1,"types":[{"name":"type_census::census::socket::tests::Foo","count":0,"#
+        ));
+        assert_eq!(lines[2], "ERR unknown type `a::Bar`");
+        assert!(lines[3].starts_with("ERR unknown request `PUT x`"));
+
+        drop(listener);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn long_requests() {
+        let path = std::env::temp_dir().join(format!(
+            "type-census-socket-long-{}.sock",
+            std::process::id()
+        ));
+        let listener = listen(&path).unwrap();
+        let stream = UnixStream::connect(listener.path()).unwrap();
+        (&stream).write_all(&[b'x'; MAX_REQUEST + 1]).unwrap();
+        let mut response = String::new();
+        BufReader::new(&stream).read_line(&mut response).unwrap();
+        assert_eq!(response, "ERR request longer than 4096 bytes\n");
+    }
+}
@@ -0,0 +1,151 @@
+//! Sources of time for time-based census features.
+//!
+//! The default, [`SystemClock`], reads [`Instant::now`]. [`MockClock`] only
+//! advances when told to, so time-dependent behavior can be tested
+//! deterministically.
+//!
+//! Clocks time the rate and window counters (e.g.
+//! [`EwmaCounter`](crate::counter::EwmaCounter)), the
+//! [lifetimes](crate::lifetime) of timed instances, the timestamps of
+//! [recorders](crate::census::recorder), and the timeout of
+//! [`block_until_zero_with_clock`](crate::census::block_until_zero_with_clock).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// A source of monotonic time.
+pub trait Clock: Send + Sync + 'static {
+    /// Produces the current instant, according to this clock.
+    fn now(&self) -> Instant;
+
+    /// Blocks the current thread for `duration`, according to this clock.
+    ///
+    /// By default, this is [`std::thread::sleep`].
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+impl<C> Clock for &'static C
+where
+    C: Clock,
+{
+    #[inline(always)]
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration);
+    }
+}
+
+/// A [`Clock`] that can be constructed in a constant.
+///
+/// Time-based counters embed their clock, and every [`Counter`] must have a
+/// constant [`ZERO`], so their clocks implement this.
+///
+/// [`Counter`]: crate::counter::Counter
+/// [`ZERO`]: crate::counter::Counter::ZERO
+pub trait ConstClock: Clock {
+    /// A new instance of this clock.
+    const NEW: Self;
+}
+
+/// A [`Clock`] reading the system's monotonic clock via [`Instant::now`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline(always)]
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+impl ConstClock for SystemClock {
+    const NEW: Self = SystemClock;
+}
+
+/// A [`Clock`] that advances only when [`MockClock::advance`] is called.
+///
+/// Every `MockClock` starts at the same process-wide origin instant, so a
+/// `MockClock` can be constructed in a `static`:
+/// ```
+/// use std::time::Duration;
+/// use type_census::clock::{Clock, MockClock};
+///
+/// static CLOCK: MockClock = MockClock::new();
+///
+/// let start = CLOCK.now();
+/// CLOCK.advance(Duration::from_secs(30));
+/// assert_eq!(CLOCK.now() - start, Duration::from_secs(30));
+/// ```
+#[derive(Debug, Default)]
+pub struct MockClock {
+    elapsed: AtomicU64,
+}
+
+impl MockClock {
+    /// Constructs a new `MockClock`, reading the process-wide origin instant.
+    pub const fn new() -> Self {
+        Self {
+            elapsed: AtomicU64::new(0),
+        }
+    }
+
+    fn origin() -> Instant {
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        *ORIGIN.get_or_init(Instant::now)
+    }
+
+    /// Advances this clock by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        let _ = self.elapsed.fetch_add(nanos, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        Self::origin() + Duration::from_nanos(self.elapsed.load(Ordering::SeqCst))
+    }
+
+    /// Advances this clock by `duration`, without blocking.
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+impl ConstClock for MockClock {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NEW: Self = Self::new();
+}
+
+#[cfg(test)]
+mod mock_clock {
+    use super::*;
+
+    #[test]
+    fn stopped() {
+        let clock = MockClock::new();
+        assert_eq!(clock.now(), clock.now());
+    }
+
+    #[test]
+    fn advance() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(clock.now() - start, Duration::from_millis(5));
+    }
+
+    #[test]
+    fn sleep() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        clock.sleep(Duration::from_secs(60));
+        assert_eq!(clock.now() - start, Duration::from_secs(60));
+    }
+}
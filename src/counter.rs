@@ -1,10 +1,54 @@
 //! Shared counters, suitable for quickly tabulating extant types.
 //!
 //! The default, [`RelaxedCounter`], is suitable in most circumstances.
+//!
+//! Every counter can be constructed in a constant with `new`, so counters can
+//! be embedded in your own `static` composites:
+//! ```
+//! use type_census::counter::{Counter, PeakCounter, RelaxedCounter};
+//!
+//! struct Pool {
+//!     checked_out: PeakCounter,
+//!     misses: RelaxedCounter,
+//! }
+//!
+//! static POOL: Pool = Pool {
+//!     checked_out: PeakCounter::new(),
+//!     misses: RelaxedCounter::new(),
+//! };
+//!
+//! POOL.checked_out.add_assign(2);
+//! POOL.checked_out.sub_assign(1);
+//! POOL.misses.add_assign(1);
+//! assert_eq!(POOL.checked_out.fetch(), 1);
+//! assert_eq!(POOL.checked_out.peak(), 2);
+//! assert_eq!(POOL.misses.fetch(), 1);
+//! ```
+//!
+//! ## Loom
+//! Under `cfg(loom)`, [`DistributedCounter`] and [`PackedCounter`] are built
+//! on [loom]'s atomics, so that code using them can be model-checked:
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --lib loom
+//! ```
+//! Loom's atomics can't be constructed in constant context, so under
+//! `cfg(loom)`, these counters must be constructed with `new` rather than
+//! [`Counter::ZERO`], and can't be placed in statics.
+//!
+//! [loom]: https://docs.rs/loom
 
+use crate::clock::{ConstClock, SystemClock};
+use crate::sync;
 use crossbeam_utils::CachePadded;
-use num_traits::Num;
-use std::sync::atomic::{AtomicIsize, Ordering};
+use num_traits::{Num, ToPrimitive};
+use std::any::TypeId;
+use std::backtrace::Backtrace;
+use std::collections::{BTreeMap, VecDeque};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError, RwLock};
+use std::thread::ThreadId;
+use std::time::Instant;
 
 /// A type suitable as a shared census counter.
 pub trait Counter: 'static {
@@ -22,45 +66,203 @@ pub trait Counter: 'static {
 
     /// Eventually retrieve the value of this counter.
     fn fetch(&self) -> Self::Primitive;
+
+    /// Retrieve the exact value of this counter at some moment during the
+    /// call.
+    ///
+    /// [`Counter::fetch`] may be approximate: a sharded counter sums its
+    /// buckets one at a time, so while it is concurrently updated, the sum
+    /// can mix values from before and after an update and even appear
+    /// negative. This may be slower than [`Counter::fetch`], as it waits a
+    /// bounded time for concurrent updates to quiesce; under updates that
+    /// never quiesce, it gives up and is no more exact than
+    /// [`Counter::fetch`]. By default, it is [`Counter::fetch`], which is
+    /// exact for counters backed by a single location.
+    fn fetch_exact(&self) -> Self::Primitive {
+        self.fetch()
+    }
+
+    /// Atomically retrieve the value of this counter and reset it to `0`.
+    ///
+    /// This lets interval-based exporters read and clear a counter of
+    /// deltas in one step, rather than differencing consecutive fetches.
+    /// Counters that can't do so atomically, such as sharded counters,
+    /// produce [`Unsupported`]; this is the default.
+    fn fetch_and_reset(&self) -> Result<Self::Primitive, Unsupported> {
+        Err(Unsupported)
+    }
+
+    /// Increase the value of this counter by `n`, and retrieve its value
+    /// from just before the increase.
+    ///
+    /// [Subscribers](crate::census::subscribe) are notified by way of this,
+    /// so that they observe exactly which thresholds an update crossed.
+    /// Counters that can't retrieve the prior value in the same atomic step
+    /// still apply the increase, and produce [`Unsupported`]; this is the
+    /// default.
+    fn fetch_add(&self, n: Self::Primitive) -> Result<Self::Primitive, Unsupported> {
+        self.add_assign(n);
+        Err(Unsupported)
+    }
+
+    /// Decrease the value of this counter by `n`, and retrieve its value
+    /// from just before the decrease.
+    ///
+    /// See [`Counter::fetch_add`].
+    fn fetch_sub(&self, n: Self::Primitive) -> Result<Self::Primitive, Unsupported> {
+        self.sub_assign(n);
+        Err(Unsupported)
+    }
+
+    /// Converts `n` to an `isize`, saturating at the bounds of `isize`.
+    ///
+    /// The [registry](crate::registry) and
+    /// [subscribers](crate::census::subscribe) report populations as
+    /// `isize`s, by way of this conversion. Every counter in this crate
+    /// implements it; by default, it produces `None`, and populations are
+    /// reported as `0`.
+    fn to_isize(n: Self::Primitive) -> Option<isize> {
+        let _ = n;
+        None
+    }
+
+    /// Eventually retrieve the number of bytes attributed to the instances
+    /// counted by this counter, if it tracks bytes.
+    ///
+    /// Counters that track bytes, such as [`BytesCounter`], report them to
+    /// the [registry](crate::registry) and its exporters by way of this. By
+    /// default, it produces `None`.
+    fn fetch_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    /// Eventually retrieve the most bytes ever attributed to the instances
+    /// counted by this counter, if it tracks bytes.
+    ///
+    /// See [`Counter::fetch_bytes`].
+    fn fetch_peak_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    /// Eventually retrieve the number of times this counter has been
+    /// decremented below zero, if it detects underflow.
+    ///
+    /// Counters that detect underflow, such as [`DiagnosticCounter`],
+    /// report it to the [registry](crate::registry) and its exporters by
+    /// way of this. By default, it produces `None`.
+    fn fetch_underflows(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Converts `n` to an `isize`, saturating at the bounds of `isize`.
+#[inline(always)]
+fn saturating_isize<N>(n: N) -> isize
+where
+    N: ToPrimitive + PartialOrd + num_traits::Zero,
+{
+    n.to_isize().unwrap_or(if n < N::zero() {
+        isize::MIN
+    } else {
+        isize::MAX
+    })
+}
+
+/// The error produced by an operation a [`Counter`] does not support.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Unsupported;
+
+impl std::fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("operation not supported by this counter")
+    }
+}
+
+impl std::error::Error for Unsupported {}
+
+/// A [`Counter`] carrying more than one value.
+pub trait FetchAll: Counter {
+    /// The values carried by this counter.
+    type Stats;
+
+    /// Eventually retrieve every value of this counter.
+    ///
+    /// The values are read together, so they are mutually consistent:
+    /// values packed into a single word are read in a single load, and
+    /// values kept in separate words are read in an order that preserves
+    /// the invariants between them (e.g., that the peak of a counter is at
+    /// least its current value), as documented by each implementation.
+    fn fetch_all(&self) -> Self::Stats;
 }
 
+/// A [`Counter`] whose full state can be saved and later restored.
+///
+/// This is intended for checkpointing deterministic simulations. Restoring
+/// a counter while it is concurrently updated loses those updates.
+pub trait Checkpoint: Counter {
+    /// The saved state of this counter.
+    type State;
+
+    /// Saves the full state of this counter.
+    fn checkpoint(&self) -> Self::State;
+
+    /// Overwrites the full state of this counter with `state`.
+    fn restore(&self, state: Self::State);
+}
+
+/// The counter used by `#[derive(Tabulate)]` when no `Counter` is named.
+///
+/// This is [`RelaxedCounter`], or [`NoopCounter`] when compiled with
+/// `--cfg type_census_disabled` (see the [crate documentation](crate)).
+#[cfg(not(type_census_disabled))]
+pub type DefaultCounter = RelaxedCounter;
+
+/// The counter used by `#[derive(Tabulate)]` when no `Counter` is named.
+///
+/// This is [`RelaxedCounter`], or [`NoopCounter`] when compiled with
+/// `--cfg type_census_disabled` (see the [crate documentation](crate)).
+#[cfg(type_census_disabled)]
+pub type DefaultCounter = NoopCounter;
+
 /// An [`AtomicIsize`] padded and aligned to the cache line size to combat
 /// [false sharing].
 ///
 /// As a [`Counter`], this type uses [`Ordering::Relaxed`] for
 /// [`Counter::add_assign`], [`Counter::sub_assign`] and [`Counter::fetch`].
+/// It is an [`AtomicCounter`] with those choices.
 ///
 /// [false sharing]: https://en.wikipedia.org/wiki/False_sharing
-#[repr(transparent)]
-pub struct RelaxedCounter {
-    counter: CachePadded<AtomicIsize>,
-}
+pub type RelaxedCounter = AtomicCounter<AtomicIsize, ordering::Relaxed>;
 
-impl Counter for RelaxedCounter {
-    type Primitive = isize;
-    const ZERO: Self = Self {
-        counter: CachePadded::new(AtomicIsize::new(0)),
-    };
+#[cfg(test)]
+mod relaxed_counter {
+    use super::*;
 
-    #[inline(always)]
-    fn add_assign(&self, n: isize) {
-        let _ = self.counter.fetch_add(n, Ordering::Relaxed);
+    #[test]
+    fn fetch_and_reset() {
+        let counter = RelaxedCounter::ZERO;
+        counter.add_assign(2);
+        assert_eq!(counter.fetch_and_reset(), Ok(2));
+        assert_eq!(counter.fetch(), 0);
     }
 
-    #[inline(always)]
-    fn sub_assign(&self, n: isize) {
-        let _ = self.counter.fetch_sub(n, Ordering::Relaxed);
+    #[test]
+    fn fetch_add() {
+        let counter = RelaxedCounter::ZERO;
+        assert_eq!(counter.fetch_add(3), Ok(0));
+        assert_eq!(counter.fetch_sub(1), Ok(3));
+        assert_eq!(counter.fetch(), 2);
     }
 
-    #[inline(always)]
-    fn fetch(&self) -> isize {
-        self.counter.load(Ordering::Relaxed)
+    #[test]
+    fn checkpoint() {
+        let counter = RelaxedCounter::ZERO;
+        counter.add_assign(2);
+        let state = counter.checkpoint();
+        counter.sub_assign(5);
+        counter.restore(state);
+        assert_eq!(counter.fetch(), 2);
     }
-}
-
-#[cfg(test)]
-mod relaxed_counter {
-    use super::*;
 
     #[test]
     fn zero() {
@@ -83,6 +285,116 @@ mod relaxed_counter {
     }
 }
 
+/// Selects one of `buckets` buckets for the current thread.
+///
+/// Threads are numbered in order of first use, and the number is scrambled
+/// by a multiplicative (Fibonacci) hash, whose high bits choose the bucket.
+/// Consecutively numbered threads, such as the workers of a pool, are thus
+/// spread across the buckets rather than packed into the lowest ones.
+#[inline(always)]
+fn thread_bucket(buckets: usize) -> usize {
+    bucket_of(sync::thread_number() as u64, buckets)
+}
+
+#[inline(always)]
+fn bucket_of(id: u64, buckets: usize) -> usize {
+    let hash = id.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    ((u128::from(hash) * buckets as u128) >> 64) as usize
+}
+
+/// The most passes [`sum_exact`] makes over the buckets of a sharded counter
+/// before giving up on two consecutive passes agreeing.
+const EXACT_PASSES: usize = 64;
+
+/// Sums the buckets of a sharded counter, repeating the pass until two
+/// consecutive passes read the same value from every bucket.
+///
+/// The sum is then the value of the counter at the moment between the two
+/// passes, unless a bucket changed and changed back in the meantime. Under
+/// continuous updates, passes may never agree, so after [`EXACT_PASSES`]
+/// passes, this gives up and produces the sum of the last pass, which is no
+/// more exact than a single pass. The values of the previous pass are kept
+/// in one buffer, allocated once per call.
+fn sum_exact<I>(buckets: impl Fn() -> I) -> isize
+where
+    I: Iterator<Item = isize>,
+{
+    let mut values: Vec<isize> = buckets().collect();
+    for _ in 1..EXACT_PASSES {
+        let mut unchanged = true;
+        let mut len = 0;
+        for current in buckets() {
+            match values.get_mut(len) {
+                Some(value) => {
+                    unchanged &= *value == current;
+                    *value = current;
+                }
+                None => {
+                    unchanged = false;
+                    values.push(current);
+                }
+            }
+            len += 1;
+        }
+        unchanged &= len == values.len();
+        values.truncate(len);
+        if unchanged {
+            break;
+        }
+        sync::hint::spin_loop();
+    }
+    values
+        .iter()
+        .fold(0isize, |sum, value| sum.wrapping_add(*value))
+}
+
+#[cfg(test)]
+mod thread_bucket {
+    use super::*;
+
+    #[test]
+    fn in_range() {
+        for buckets in 1..=17 {
+            for id in 0..100 {
+                assert!(bucket_of(id, buckets) < buckets);
+            }
+        }
+    }
+
+    #[test]
+    fn spread() {
+        let mut counts = [0; 8];
+        for id in 0..1024 {
+            counts[bucket_of(id, 8)] += 1;
+        }
+        assert!(counts.iter().all(|&count| (112..=144).contains(&count)));
+    }
+
+    #[test]
+    fn sum_exact_gives_up() {
+        let passes = std::cell::Cell::new(0isize);
+        let sum = sum_exact(|| {
+            passes.set(passes.get() + 1);
+            [1, passes.get()].into_iter()
+        });
+        assert_eq!(passes.get(), EXACT_PASSES as isize);
+        assert_eq!(sum, 1 + EXACT_PASSES as isize);
+    }
+}
+
+/// Attempts to add `n` to one bucket of a sharded counter, failing if the
+/// bucket is contended.
+#[inline(always)]
+fn try_add_assign(bucket: &AtomicIsize, n: isize) -> Result<isize, isize> {
+    let count = bucket.load(Ordering::Relaxed);
+    bucket.compare_exchange_weak(
+        count,
+        count.wrapping_add(n),
+        Ordering::Relaxed,
+        Ordering::Relaxed,
+    )
+}
+
 /// A counter that minimizes slowdowns from contenation at the cost of increased
 /// memory usage.
 ///
@@ -90,80 +402,159 @@ mod relaxed_counter {
 /// Use this counter type only if [`RelaxedCounter`] performs poorly. Then,
 /// benchmark the performance of your code with [`DistributedCounter`] with
 /// a bucket count of `1`. Increase the number of buckets (up to your
-/// available parallelism) until performance is satisfactory. 
+/// available parallelism) until performance is satisfactory. If the
+/// deployment hardware isn't known ahead of time, use
+/// [`DynDistributedCounter`] instead.
+///
+/// Every operation uses [`Ordering::Relaxed`]. Each thread updates its own
+/// bucket with a single `fetch_add`, so no update is lost, but
+/// [`Counter::fetch`] reads the buckets one at a time: while the counter is
+/// concurrently updated, the sum may not match the value of the counter at
+/// any single point in time, and may even appear negative. Use
+/// [`Counter::fetch_exact`] for a best-effort consistent read.
+///
+/// A consistent [`Counter::fetch`] would need every update to take a
+/// sequence lock or bump a shared epoch, which costs the uncontended
+/// updates this counter exists to provide, and makes reads wait on writers;
+/// this counter deliberately doesn't.
 ///
 /// [multi]: https://travisdowns.github.io/blog/2020/07/06/concurrency-costs.html#adaptive-multi-counter
 pub struct DistributedCounter<const BUCKETS: usize> {
-    counters: [CachePadded<AtomicIsize>; BUCKETS],
+    counters: [CachePadded<sync::atomic::AtomicIsize>; BUCKETS],
 }
 
 impl<const BUCKETS: usize> DistributedCounter<BUCKETS> {
-    const fn new() -> Self {
+    /// Constructs a new counter holding the value of `0`.
+    #[cfg(not(loom))]
+    pub const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
         const BUCKET: CachePadded<AtomicIsize> = CachePadded::new(AtomicIsize::new(0));
         Self {
             counters: [BUCKET; BUCKETS],
         }
     }
 
-    fn thread_id() -> usize {
-        use std::sync::atomic::AtomicUsize;
-        static THREADS: AtomicUsize = AtomicUsize::new(0);
-        thread_local! {
-            pub static ID: usize = THREADS.fetch_add(1, Ordering::SeqCst);
+    /// Constructs a new counter holding the value of `0`.
+    #[cfg(loom)]
+    pub fn new() -> Self {
+        Self {
+            counters: std::array::from_fn(|_| CachePadded::new(sync::atomic::AtomicIsize::new(0))),
         }
-        ID.try_with(|id| *id).unwrap_or(0)
     }
 
     #[inline(always)]
-    fn try_add_assign(bucket: &AtomicIsize, n: isize) -> Result<isize, isize> {
-        let count = bucket.load(Ordering::SeqCst);
-        bucket.compare_exchange_weak(
-            count,
-            count.wrapping_add(n),
-            Ordering::SeqCst,
-            Ordering::SeqCst,
-        )
+    fn add_assign(&self, n: isize) {
+        let bucket = &self.counters[thread_bucket(BUCKETS)];
+        let _ = bucket.fetch_add(n, Ordering::Relaxed);
     }
+}
 
-    #[inline(always)]
-    fn add_assign(&self, n: isize) {
-        let id = Self::thread_id();
-        let mut bucket = id % BUCKETS;
-        loop {
-            if Self::try_add_assign(&self.counters[bucket], n).is_ok() {
-                return;
-            } else {
-                bucket = bucket.wrapping_add(1) % BUCKETS;
-            }
-        }
+impl<const BUCKETS: usize> Default for DistributedCounter<BUCKETS> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl<const BUCKETS: usize> Counter for DistributedCounter<BUCKETS> {
     type Primitive = isize;
+    #[cfg(not(loom))]
     const ZERO: Self = Self::new();
+    #[cfg(loom)]
+    const ZERO: Self = panic!("loom's atomics can't be constructed in constant context");
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
 
+    #[inline(always)]
     fn add_assign(&self, n: isize) {
         self.add_assign(n)
     }
 
+    #[inline(always)]
     fn sub_assign(&self, n: isize) {
-        self.add_assign(-n)
+        self.add_assign(n.wrapping_neg())
     }
 
     fn fetch(&self) -> isize {
-        let mut sum = 0isize;
-        for counter in &self.counters {
-            sum = sum.wrapping_add(counter.load(Ordering::SeqCst));
+        self.counters.iter().fold(0isize, |sum, counter| {
+            sum.wrapping_add(counter.load(Ordering::Relaxed))
+        })
+    }
+
+    fn fetch_exact(&self) -> isize {
+        sum_exact(|| {
+            self.counters
+                .iter()
+                .map(|counter| counter.load(Ordering::Acquire))
+        })
+    }
+}
+
+impl<const BUCKETS: usize> Checkpoint for DistributedCounter<BUCKETS> {
+    type State = [isize; BUCKETS];
+
+    fn checkpoint(&self) -> [isize; BUCKETS] {
+        let mut state = [0; BUCKETS];
+        for (bucket, counter) in state.iter_mut().zip(&self.counters) {
+            *bucket = counter.load(Ordering::Relaxed);
+        }
+        state
+    }
+
+    fn restore(&self, state: [isize; BUCKETS]) {
+        for (bucket, counter) in state.iter().zip(&self.counters) {
+            counter.store(*bucket, Ordering::Relaxed);
         }
-        sum
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, not(loom)))]
 mod distributed_counter {
     use super::*;
 
+    #[test]
+    fn fetch_add() {
+        let counter = DistributedCounter::<4>::ZERO;
+        assert_eq!(counter.fetch_add(3), Err(Unsupported));
+        assert_eq!(counter.fetch_sub(1), Err(Unsupported));
+        assert_eq!(counter.fetch(), 2);
+    }
+
+    #[test]
+    fn fetch_exact() {
+        let counter = DistributedCounter::<4>::ZERO;
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..1000 {
+                        counter.add_assign(1);
+                        assert!(counter.fetch_exact() >= 0);
+                        counter.sub_assign(1);
+                    }
+                });
+            }
+        });
+        assert_eq!(counter.fetch_exact(), 0);
+    }
+
+    #[test]
+    fn fetch_and_reset() {
+        let counter = DistributedCounter::<4>::ZERO;
+        assert_eq!(counter.fetch_and_reset(), Err(Unsupported));
+    }
+
+    #[test]
+    fn checkpoint() {
+        let counter = DistributedCounter::<4>::ZERO;
+        counter.add_assign(2);
+        let state = counter.checkpoint();
+        counter.sub_assign(5);
+        counter.restore(state);
+        assert_eq!(counter.fetch(), 2);
+    }
+
     #[test]
     fn zero() {
         let counter = DistributedCounter::<1>::ZERO;
@@ -185,41 +576,148 @@ mod distributed_counter {
     }
 }
 
-/// A [`Counter`] useful for testing.
+/// A [`DistributedCounter`] whose bucket count is chosen at runtime.
 ///
-/// This counter uses [`Ordering::SeqCst`] for [`Counter::add_assign`],
-/// [`Counter::sub_assign`] and [`Counter::fetch`].
-#[repr(transparent)]
-pub struct SeqCstCounter {
-    counter: CachePadded<AtomicIsize>,
+/// The buckets are allocated on first use, one per unit of
+/// [available parallelism] of the machine, so the bucket count needn't be
+/// guessed ahead of deployment. The buckets are never freed.
+///
+/// Like [`DistributedCounter`], every operation uses [`Ordering::Relaxed`].
+///
+/// [available parallelism]: std::thread::available_parallelism
+pub struct DynDistributedCounter {
+    counters: std::sync::OnceLock<Box<[CachePadded<AtomicIsize>]>>,
 }
 
-impl Counter for SeqCstCounter {
-    type Primitive = isize;
-    const ZERO: Self = Self {
-        counter: CachePadded::new(AtomicIsize::new(0)),
-    };
+impl DynDistributedCounter {
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            counters: std::sync::OnceLock::new(),
+        }
+    }
+
+    fn counters(&self) -> &[CachePadded<AtomicIsize>] {
+        self.counters.get_or_init(|| {
+            let buckets = std::thread::available_parallelism().map_or(1, |n| n.get());
+            (0..buckets)
+                .map(|_| CachePadded::new(AtomicIsize::new(0)))
+                .collect()
+        })
+    }
+
+    /// Produces the number of buckets of this counter.
+    pub fn buckets(&self) -> usize {
+        self.counters().len()
+    }
 
     #[inline(always)]
     fn add_assign(&self, n: isize) {
-        let _ = self.counter.fetch_add(n, Ordering::SeqCst);
+        let counters = self.counters();
+        let mut bucket = thread_bucket(counters.len());
+        loop {
+            if try_add_assign(&counters[bucket], n).is_ok() {
+                return;
+            } else {
+                bucket = bucket.wrapping_add(1) % counters.len();
+            }
+        }
+    }
+}
+
+impl Default for DynDistributedCounter {
+    fn default() -> Self {
+        Self::new()
     }
+}
+
+impl Counter for DynDistributedCounter {
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
 
     #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    fn add_assign(&self, n: isize) {
+        self.add_assign(n)
+    }
+
     fn sub_assign(&self, n: isize) {
-        let _ = self.counter.fetch_sub(n, Ordering::SeqCst);
+        self.add_assign(-n)
     }
 
-    #[inline(always)]
     fn fetch(&self) -> isize {
-        self.counter.load(Ordering::SeqCst)
+        let mut sum = 0isize;
+        for counter in self.counters() {
+            sum = sum.wrapping_add(counter.load(Ordering::Relaxed));
+        }
+        sum
+    }
+
+    fn fetch_exact(&self) -> isize {
+        sum_exact(|| {
+            self.counters()
+                .iter()
+                .map(|counter| counter.load(Ordering::Acquire))
+        })
+    }
+}
+
+#[cfg(test)]
+mod dyn_distributed_counter {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = DynDistributedCounter::ZERO;
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = DynDistributedCounter::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = DynDistributedCounter::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+    }
+
+    #[test]
+    fn buckets() {
+        let counter = DynDistributedCounter::ZERO;
+        let parallelism = std::thread::available_parallelism().map_or(1, |n| n.get());
+        assert_eq!(counter.buckets(), parallelism);
     }
 }
 
+/// A [`Counter`] useful for testing.
+///
+/// This counter uses [`Ordering::SeqCst`] for [`Counter::add_assign`],
+/// [`Counter::sub_assign`] and [`Counter::fetch`]. It is an
+/// [`AtomicCounter`] with those choices.
+pub type SeqCstCounter = AtomicCounter<AtomicIsize, ordering::SeqCst>;
+
 #[cfg(test)]
 mod seqcst_counter {
     use super::*;
 
+    #[test]
+    fn checkpoint() {
+        let counter = SeqCstCounter::ZERO;
+        counter.add_assign(2);
+        let state = counter.checkpoint();
+        counter.sub_assign(5);
+        counter.restore(state);
+        assert_eq!(counter.fetch(), 2);
+    }
+
     #[test]
     fn zero() {
         let counter = SeqCstCounter::ZERO;
@@ -240,3 +738,4002 @@ mod seqcst_counter {
         assert_eq!(counter.fetch(), -1);
     }
 }
+
+/// A [`Counter`] that packs a 32-bit live count and a 32-bit cumulative
+/// creation count into a single [`AtomicU64`].
+///
+/// Both values are updated by a single [`Ordering::Relaxed`] read-modify-write
+/// per operation, so [`PackedCounter::created`] is available with no extra
+/// atomic traffic. The live count must fit in an [`i32`]; the cumulative
+/// count wraps around after [`u32::MAX`] creations. This counter is only
+/// available on targets with 64-bit atomics.
+#[cfg(target_has_atomic = "64")]
+#[repr(transparent)]
+pub struct PackedCounter {
+    counter: CachePadded<sync::atomic::AtomicU64>,
+}
+
+#[cfg(target_has_atomic = "64")]
+impl PackedCounter {
+    /// Constructs a new counter holding the value of `0`.
+    #[cfg(not(loom))]
+    pub const fn new() -> Self {
+        Self {
+            counter: CachePadded::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Constructs a new counter holding the value of `0`.
+    #[cfg(loom)]
+    pub fn new() -> Self {
+        Self {
+            counter: CachePadded::new(sync::atomic::AtomicU64::new(0)),
+        }
+    }
+
+    /// Splits a packed value into its live and cumulative components.
+    ///
+    /// The packed value is `created * 2^32 + live`, in wrapping arithmetic;
+    /// a negative live count borrows from the cumulative half, which is
+    /// undone here.
+    #[inline(always)]
+    fn unpack(packed: u64) -> (i32, u32) {
+        let live = packed as u32 as i32;
+        let created = packed.wrapping_sub(live as i64 as u64) >> 32;
+        (live, created as u32)
+    }
+
+    /// Eventually retrieve the total number of increments applied to this
+    /// counter.
+    #[inline(always)]
+    pub fn created(&self) -> u32 {
+        Self::unpack(self.counter.load(Ordering::Relaxed)).1
+    }
+}
+
+/// The values carried by a [`PackedCounter`].
+#[cfg(target_has_atomic = "64")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackedStats {
+    /// The current value of the counter.
+    pub live: i32,
+    /// The total number of increments applied to the counter.
+    pub created: u32,
+}
+
+#[cfg(target_has_atomic = "64")]
+impl FetchAll for PackedCounter {
+    type Stats = PackedStats;
+
+    #[inline(always)]
+    fn fetch_all(&self) -> PackedStats {
+        let (live, created) = Self::unpack(self.counter.load(Ordering::Relaxed));
+        PackedStats { live, created }
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl Checkpoint for PackedCounter {
+    type State = PackedStats;
+
+    fn checkpoint(&self) -> PackedStats {
+        self.fetch_all()
+    }
+
+    fn restore(&self, state: PackedStats) {
+        let packed = ((state.created as u64) << 32).wrapping_add(state.live as i64 as u64);
+        self.counter.store(packed, Ordering::Relaxed)
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl Default for PackedCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl Counter for PackedCounter {
+    type Primitive = i32;
+    #[cfg(not(loom))]
+    const ZERO: Self = Self::new();
+    #[cfg(loom)]
+    const ZERO: Self = panic!("loom's atomics can't be constructed in constant context");
+
+    #[inline(always)]
+    fn to_isize(n: i32) -> Option<isize> {
+        Some(n as isize)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: i32) {
+        let n = n as i64;
+        let delta = (n << 32).wrapping_add(n) as u64;
+        let _ = self.counter.fetch_add(delta, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: i32) {
+        let _ = self.counter.fetch_sub(n as i64 as u64, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> i32 {
+        Self::unpack(self.counter.load(Ordering::Relaxed)).0
+    }
+}
+
+#[cfg(all(test, target_has_atomic = "64", not(loom)))]
+mod packed_counter {
+    use super::*;
+
+    #[test]
+    fn checkpoint() {
+        let counter = PackedCounter::ZERO;
+        counter.add_assign(2);
+        let state = counter.checkpoint();
+        counter.sub_assign(5);
+        counter.restore(state);
+        assert_eq!(counter.fetch(), 2);
+        assert_eq!(counter.created(), 2);
+    }
+
+    #[test]
+    fn zero() {
+        let counter = PackedCounter::ZERO;
+        assert_eq!(counter.fetch(), 0);
+        assert_eq!(counter.created(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = PackedCounter::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+        assert_eq!(counter.created(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = PackedCounter::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+        assert_eq!(counter.created(), 0);
+    }
+
+    #[test]
+    fn churn() {
+        let counter = PackedCounter::ZERO;
+        counter.add_assign(3);
+        counter.sub_assign(5);
+        counter.add_assign(4);
+        assert_eq!(counter.fetch(), 2);
+        assert_eq!(counter.created(), 7);
+    }
+
+    #[test]
+    fn fetch_all() {
+        let counter = PackedCounter::ZERO;
+        counter.add_assign(2);
+        counter.sub_assign(1);
+        assert_eq!(
+            counter.fetch_all(),
+            PackedStats {
+                live: 1,
+                created: 2
+            }
+        );
+    }
+}
+
+/// A set of counters, one per type.
+///
+/// This is used by `#[Tabulate(per_param(T))]` to count instances
+/// separately for each instantiation of the generic parameter `T`. Counters
+/// are allocated on first use and never freed.
+pub struct PerType<C>
+where
+    C: Counter,
+{
+    counters: RwLock<BTreeMap<TypeId, &'static TypeSlot<C>>>,
+}
+
+/// The counter of one type in a [`PerType`], and whether its type has been
+/// registered.
+struct TypeSlot<C> {
+    counter: C,
+    registered: AtomicBool,
+}
+
+impl<C> PerType<C>
+where
+    C: Counter,
+{
+    /// Constructs a new, empty set of counters.
+    pub const fn new() -> Self {
+        Self {
+            counters: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Produces the slot associated with `T`.
+    fn slot<T>(&self) -> &'static TypeSlot<C>
+    where
+        T: ?Sized + 'static,
+    {
+        let key = TypeId::of::<T>();
+        let counters = self.counters.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(slot) = counters.get(&key) {
+            return slot;
+        }
+        drop(counters);
+        let mut counters = self
+            .counters
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        counters.entry(key).or_insert_with(|| {
+            Box::leak(Box::new(TypeSlot {
+                counter: C::ZERO,
+                registered: AtomicBool::new(false),
+            }))
+        })
+    }
+
+    /// Produces the counter associated with `T`.
+    pub fn get<T>(&self) -> &'static C
+    where
+        T: ?Sized + 'static,
+    {
+        &self.slot::<T>().counter
+    }
+
+    /// Registers `S`, whose instances are counted by the counter associated
+    /// with `T`, in the global [registry](crate::registry), if it hasn't
+    /// been already.
+    ///
+    /// Whether each counter's type has been registered is recorded with the
+    /// counter, so after the first call, this doesn't touch the registry.
+    pub fn ensure_registered<T, S>(&self)
+    where
+        T: ?Sized + 'static,
+        S: crate::Tabulate<Counter = C>,
+    {
+        let slot = self.slot::<T>();
+        if !slot.registered.swap(true, Ordering::Relaxed) {
+            crate::registry().insert::<S>(None);
+        }
+    }
+}
+
+impl<C> Default for PerType<C>
+where
+    C: Counter,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod per_type {
+    use super::*;
+
+    #[test]
+    fn distinct() {
+        let counters = PerType::<RelaxedCounter>::new();
+        counters.get::<u8>().add_assign(1);
+        assert_eq!(counters.get::<u8>().fetch(), 1);
+        assert_eq!(counters.get::<i8>().fetch(), 0);
+    }
+
+    #[test]
+    fn shared() {
+        let counters = PerType::<RelaxedCounter>::new();
+        assert!(std::ptr::eq(counters.get::<u8>(), counters.get::<u8>()));
+    }
+}
+
+/// A record of a [`DiagnosticCounter`] being decremented below zero.
+///
+/// Underflow means something double-decremented: an instance was dropped
+/// that was never counted, or was counted once and dropped twice.
+#[derive(Debug)]
+pub struct Underflow {
+    /// The value of the counter after the offending decrement.
+    pub value: isize,
+    /// The id of the thread that performed the offending decrement.
+    pub thread_id: ThreadId,
+    /// The name of the thread that performed the offending decrement, if
+    /// it has one.
+    pub thread_name: Option<String>,
+    /// The backtrace of the offending decrement.
+    ///
+    /// This is captured with [`Backtrace::capture`], so it is only
+    /// populated if backtraces are enabled (e.g., with `RUST_BACKTRACE=1`).
+    pub backtrace: Backtrace,
+}
+
+/// A [`Counter`] that records every decrement that takes it below zero.
+///
+/// This behaves like [`RelaxedCounter`], but each decrement that leaves the
+/// counter negative is recorded as an [`Underflow`], retrievable with
+/// [`DiagnosticCounter::take_underflows`]. Recording is slow, but only
+/// happens on underflow, which indicates a bug.
+///
+/// Only the last 64 records are retained, so a type that underflows
+/// repeatedly can't exhaust memory; every underflow is still counted, and
+/// the count is reported to the [registry](crate::registry) and
+/// [snapshots](crate::snapshot::Snapshot::underflows) by way of
+/// [`Counter::fetch_underflows`].
+///
+/// ```
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::DiagnosticCounter")]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// let foo = Foo { _instance: Instance::new() };
+/// drop(foo);
+///
+/// for underflow in Foo::counter().take_underflows() {
+///     eprintln!("{:?} underflowed to {}", underflow.thread_name, underflow.value);
+///     eprintln!("{}", underflow.backtrace);
+/// }
+/// ```
+pub struct DiagnosticCounter {
+    counter: CachePadded<AtomicIsize>,
+    underflows: Mutex<VecDeque<Underflow>>,
+    total: AtomicU64,
+}
+
+impl DiagnosticCounter {
+    /// The most [`Underflow`]s retained between calls to
+    /// [`DiagnosticCounter::take_underflows`].
+    pub const RETAINED: usize = 64;
+
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            counter: CachePadded::new(AtomicIsize::new(0)),
+            underflows: Mutex::new(VecDeque::new()),
+            total: AtomicU64::new(0),
+        }
+    }
+
+    /// Takes the last [`RETAINED`](Self::RETAINED) [`Underflow`]s recorded
+    /// since the last call, oldest first.
+    pub fn take_underflows(&self) -> Vec<Underflow> {
+        let mut underflows = self
+            .underflows
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        std::mem::take(&mut *underflows).into()
+    }
+
+    #[cold]
+    fn record(&self, value: isize) {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        let thread = std::thread::current();
+        let underflow = Underflow {
+            value,
+            thread_id: thread.id(),
+            thread_name: thread.name().map(String::from),
+            backtrace: Backtrace::capture(),
+        };
+        let mut underflows = self
+            .underflows
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if underflows.len() == Self::RETAINED {
+            underflows.pop_front();
+        }
+        underflows.push_back(underflow);
+    }
+}
+
+impl Default for DiagnosticCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Counter for DiagnosticCounter {
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        let _ = self.counter.fetch_add(n, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        let value = self.counter.fetch_sub(n, Ordering::Relaxed).wrapping_sub(n);
+        if value < 0 {
+            self.record(value);
+        }
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> isize {
+        self.counter.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    fn fetch_and_reset(&self) -> Result<isize, Unsupported> {
+        Ok(self.counter.swap(0, Ordering::Relaxed))
+    }
+
+    fn fetch_underflows(&self) -> Option<u64> {
+        Some(self.total.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod diagnostic_counter {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = DiagnosticCounter::ZERO;
+        assert_eq!(counter.fetch(), 0);
+        assert!(counter.take_underflows().is_empty());
+    }
+
+    #[test]
+    fn balanced() {
+        let counter = DiagnosticCounter::ZERO;
+        counter.add_assign(1);
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), 0);
+        assert!(counter.take_underflows().is_empty());
+    }
+
+    #[test]
+    fn underflow() {
+        let counter = DiagnosticCounter::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+        let underflows = counter.take_underflows();
+        assert_eq!(underflows.len(), 1);
+        assert_eq!(underflows[0].value, -1);
+        assert_eq!(underflows[0].thread_id, std::thread::current().id());
+        assert!(counter.take_underflows().is_empty());
+        assert_eq!(counter.fetch_underflows(), Some(1));
+    }
+
+    #[test]
+    fn bounded() {
+        let counter = DiagnosticCounter::ZERO;
+        for _ in 0..DiagnosticCounter::RETAINED + 10 {
+            counter.sub_assign(1);
+        }
+        let underflows = counter.take_underflows();
+        assert_eq!(underflows.len(), DiagnosticCounter::RETAINED);
+        assert_eq!(underflows[0].value, -11);
+        assert_eq!(
+            counter.fetch_underflows(),
+            Some(DiagnosticCounter::RETAINED as u64 + 10)
+        );
+    }
+}
+
+/// A sharded counter with one bucket per worker of the [rayon] thread pool.
+///
+/// The buckets are allocated on first use, sized to the rayon thread pool
+/// of the thread that first touches the counter (ordinarily the global
+/// pool), plus one bucket shared by threads outside any pool. Each pool
+/// worker updates its own bucket with [`Ordering::Relaxed`], so
+/// data-parallel workloads don't contend, and
+/// [`RayonCounter::per_worker`] reveals how many instances each worker
+/// retains.
+///
+/// [rayon]: https://docs.rs/rayon
+#[cfg(feature = "rayon")]
+pub struct RayonCounter {
+    buckets: std::sync::OnceLock<Box<[CachePadded<AtomicIsize>]>>,
+}
+
+#[cfg(feature = "rayon")]
+impl RayonCounter {
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            buckets: std::sync::OnceLock::new(),
+        }
+    }
+
+    fn buckets(&self) -> &[CachePadded<AtomicIsize>] {
+        self.buckets.get_or_init(|| {
+            (0..=rayon::current_num_threads())
+                .map(|_| CachePadded::new(AtomicIsize::new(0)))
+                .collect()
+        })
+    }
+
+    #[inline(always)]
+    fn bucket(&self) -> &AtomicIsize {
+        let buckets = self.buckets();
+        let external = buckets.len() - 1;
+        let index = rayon::current_thread_index().map_or(external, |index| index % external);
+        &buckets[index]
+    }
+
+    /// Eventually retrieve the value of each worker's bucket, in worker
+    /// index order.
+    pub fn per_worker(&self) -> Vec<isize> {
+        let buckets = self.buckets();
+        buckets[..buckets.len() - 1]
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    /// Eventually retrieve the value of the bucket shared by threads outside
+    /// the thread pool.
+    pub fn outside_pool(&self) -> isize {
+        let buckets = self.buckets();
+        buckets[buckets.len() - 1].load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl Default for RayonCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl Counter for RayonCounter {
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        let _ = self.bucket().fetch_add(n, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        let _ = self.bucket().fetch_sub(n, Ordering::Relaxed);
+    }
+
+    fn fetch(&self) -> isize {
+        self.buckets().iter().fold(0isize, |sum, bucket| {
+            sum.wrapping_add(bucket.load(Ordering::Relaxed))
+        })
+    }
+
+    fn fetch_exact(&self) -> isize {
+        sum_exact(|| {
+            self.buckets()
+                .iter()
+                .map(|bucket| bucket.load(Ordering::Acquire))
+        })
+    }
+}
+
+#[cfg(all(test, feature = "rayon"))]
+mod rayon_counter {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = RayonCounter::ZERO;
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = RayonCounter::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+        assert_eq!(counter.outside_pool(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = RayonCounter::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+    }
+
+    #[test]
+    fn per_worker() {
+        let counter = RayonCounter::ZERO;
+        rayon::broadcast(|_| counter.add_assign(1));
+        assert_eq!(counter.per_worker(), vec![1; rayon::current_num_threads()]);
+        assert_eq!(counter.outside_pool(), 0);
+        assert_eq!(counter.fetch(), rayon::current_num_threads() as isize);
+    }
+}
+
+/// An [`AtomicU64`] padded and aligned to the cache line size, whose value
+/// never goes below zero.
+///
+/// As a [`Counter`], this type uses [`Ordering::Relaxed`], and its
+/// [`Counter::sub_assign`] saturates at `0` rather than wrapping. This
+/// suits metrics pipelines that expect unsigned populations; a decrement
+/// that would go below zero indicates a bug, and is silently absorbed.
+///
+/// This counter is only available on targets with 64-bit atomics.
+#[cfg(target_has_atomic = "64")]
+#[repr(transparent)]
+pub struct UnsignedCounter {
+    counter: CachePadded<AtomicU64>,
+}
+
+#[cfg(target_has_atomic = "64")]
+impl UnsignedCounter {
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            counter: CachePadded::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl Default for UnsignedCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl Counter for UnsignedCounter {
+    type Primitive = u64;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: u64) -> Option<isize> {
+        Some(saturating_isize(n))
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: u64) {
+        let _ = self.counter.fetch_add(n, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: u64) {
+        let _ = self
+            .counter
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                Some(count.saturating_sub(n))
+            });
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> u64 {
+        self.counter.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    fn fetch_and_reset(&self) -> Result<u64, Unsupported> {
+        Ok(self.counter.swap(0, Ordering::Relaxed))
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl Checkpoint for UnsignedCounter {
+    type State = u64;
+
+    fn checkpoint(&self) -> u64 {
+        self.counter.load(Ordering::Relaxed)
+    }
+
+    fn restore(&self, state: u64) {
+        self.counter.store(state, Ordering::Relaxed)
+    }
+}
+
+#[cfg(all(test, target_has_atomic = "64"))]
+mod unsigned_counter {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = UnsignedCounter::ZERO;
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = UnsignedCounter::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = UnsignedCounter::ZERO;
+        counter.add_assign(2);
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn saturate() {
+        let counter = UnsignedCounter::ZERO;
+        counter.add_assign(1);
+        counter.sub_assign(3);
+        assert_eq!(counter.fetch(), 0);
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+}
+
+/// An atomic integer suitable as the backing storage of an
+/// [`AtomicCounter`].
+///
+/// This is implemented for each of the integer atomics in
+/// [`std::sync::atomic`] that the target supports; the 64-bit atomics are
+/// only available on targets with `target_has_atomic = "64"`.
+pub trait Atomic: Send + Sync + 'static {
+    /// The integer type underlying this atomic.
+    type Primitive: Num + ToPrimitive + PartialOrd;
+
+    /// This atomic, holding the value of `0`.
+    const ZERO: Self;
+
+    /// Adds `n` to this atomic, wrapping on overflow.
+    fn fetch_add(&self, n: Self::Primitive, order: Ordering) -> Self::Primitive;
+
+    /// Subtracts `n` from this atomic, wrapping on overflow.
+    fn fetch_sub(&self, n: Self::Primitive, order: Ordering) -> Self::Primitive;
+
+    /// Loads the value of this atomic.
+    fn load(&self, order: Ordering) -> Self::Primitive;
+
+    /// Stores `n` into this atomic, producing its previous value.
+    fn swap(&self, n: Self::Primitive, order: Ordering) -> Self::Primitive;
+}
+
+macro_rules! impl_atomic {
+    ($($(#[$attr:meta])* $atomic:ident: $primitive:ty),* $(,)?) => {$(
+        $(#[$attr])*
+        impl Atomic for std::sync::atomic::$atomic {
+            type Primitive = $primitive;
+            #[allow(clippy::declare_interior_mutable_const)]
+            const ZERO: Self = Self::new(0);
+
+            #[inline(always)]
+            fn fetch_add(&self, n: $primitive, order: Ordering) -> $primitive {
+                self.fetch_add(n, order)
+            }
+
+            #[inline(always)]
+            fn fetch_sub(&self, n: $primitive, order: Ordering) -> $primitive {
+                self.fetch_sub(n, order)
+            }
+
+            #[inline(always)]
+            fn load(&self, order: Ordering) -> $primitive {
+                self.load(order)
+            }
+
+            #[inline(always)]
+            fn swap(&self, n: $primitive, order: Ordering) -> $primitive {
+                self.swap(n, order)
+            }
+        }
+    )*};
+}
+
+impl_atomic! {
+    AtomicI8: i8,
+    AtomicI16: i16,
+    AtomicI32: i32,
+    #[cfg(target_has_atomic = "64")]
+    AtomicI64: i64,
+    AtomicIsize: isize,
+    AtomicU8: u8,
+    AtomicU16: u16,
+    AtomicU32: u32,
+    #[cfg(target_has_atomic = "64")]
+    AtomicU64: u64,
+    AtomicUsize: usize,
+}
+
+/// Memory orderings for [`AtomicCounter`], as marker types.
+pub mod ordering {
+    use std::sync::atomic::Ordering;
+
+    /// A choice of memory orderings for the operations of an
+    /// [`AtomicCounter`](super::AtomicCounter).
+    pub trait MemoryOrdering: 'static {
+        /// The ordering of [`Counter::add_assign`](super::Counter::add_assign)
+        /// and [`Counter::sub_assign`](super::Counter::sub_assign).
+        const RMW: Ordering;
+
+        /// The ordering of [`Counter::fetch`](super::Counter::fetch).
+        const LOAD: Ordering;
+    }
+
+    macro_rules! orderings {
+        ($($(#[$attr:meta])* $name:ident: $rmw:ident, $load:ident;)*) => {$(
+            $(#[$attr])*
+            #[derive(Clone, Copy, Debug, Default)]
+            pub struct $name;
+
+            impl MemoryOrdering for $name {
+                const RMW: Ordering = Ordering::$rmw;
+                const LOAD: Ordering = Ordering::$load;
+            }
+        )*};
+    }
+
+    orderings! {
+        /// Updates and reads use [`Ordering::Relaxed`].
+        Relaxed: Relaxed, Relaxed;
+        /// Updates use [`Ordering::Release`], and reads use
+        /// [`Ordering::Acquire`], so a read that observes an update also
+        /// observes everything the updating thread did before it.
+        Release: Release, Acquire;
+        /// Updates use [`Ordering::AcqRel`], and reads use
+        /// [`Ordering::Acquire`].
+        AcqRel: AcqRel, Acquire;
+        /// Updates and reads use [`Ordering::SeqCst`].
+        SeqCst: SeqCst, SeqCst;
+    }
+}
+
+/// A [`Counter`] backed by an [`Atomic`] of your choosing, padded and
+/// aligned to the cache line size, with the memory ordering of your
+/// choosing.
+///
+/// Its [`Counter::Primitive`] is that of `A`, so targets without efficient
+/// pointer-width atomics, and metrics consumers that expect a particular
+/// width, can pick their integer type. Its operations use the orderings of
+/// `O`, one of the markers in [`ordering`]. [`RelaxedCounter`] and
+/// [`SeqCstCounter`] are aliases of `AtomicCounter<AtomicIsize>` and
+/// `AtomicCounter<AtomicIsize, ordering::SeqCst>`, respectively.
+///
+/// ```
+/// use std::sync::atomic::AtomicI32;
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::AtomicCounter<AtomicI32>")]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::AtomicCounter<
+///     std::sync::atomic::AtomicIsize,
+///     type_census::counter::ordering::Release,
+/// >")]
+/// pub struct Bar {
+///     _instance: Instance<Self>,
+/// }
+///
+/// let _foo = Foo { _instance: Instance::new() };
+/// let count: i32 = Foo::instances();
+/// assert_eq!(count, 1);
+/// ```
+#[repr(transparent)]
+pub struct AtomicCounter<A, O = ordering::Relaxed> {
+    counter: CachePadded<A>,
+    ordering: PhantomData<fn() -> O>,
+}
+
+impl<A, O> AtomicCounter<A, O>
+where
+    A: Atomic,
+    O: ordering::MemoryOrdering,
+{
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            counter: CachePadded::new(A::ZERO),
+            ordering: PhantomData,
+        }
+    }
+}
+
+impl<A, O> Default for AtomicCounter<A, O>
+where
+    A: Atomic,
+    O: ordering::MemoryOrdering,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A, O> Counter for AtomicCounter<A, O>
+where
+    A: Atomic,
+    O: ordering::MemoryOrdering,
+{
+    type Primitive = A::Primitive;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: A::Primitive) -> Option<isize> {
+        Some(saturating_isize(n))
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: A::Primitive) {
+        let _ = self.counter.fetch_add(n, O::RMW);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: A::Primitive) {
+        let _ = self.counter.fetch_sub(n, O::RMW);
+    }
+
+    #[inline(always)]
+    fn fetch_add(&self, n: A::Primitive) -> Result<A::Primitive, Unsupported> {
+        Ok(self.counter.fetch_add(n, O::RMW))
+    }
+
+    #[inline(always)]
+    fn fetch_sub(&self, n: A::Primitive) -> Result<A::Primitive, Unsupported> {
+        Ok(self.counter.fetch_sub(n, O::RMW))
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> A::Primitive {
+        self.counter.load(O::LOAD)
+    }
+
+    #[inline(always)]
+    fn fetch_and_reset(&self) -> Result<A::Primitive, Unsupported> {
+        Ok(self.counter.swap(num_traits::zero(), O::RMW))
+    }
+}
+
+impl<A, O> Checkpoint for AtomicCounter<A, O>
+where
+    A: Atomic,
+    O: ordering::MemoryOrdering,
+{
+    type State = A::Primitive;
+
+    fn checkpoint(&self) -> A::Primitive {
+        self.counter.load(O::LOAD)
+    }
+
+    fn restore(&self, state: A::Primitive) {
+        let _ = self.counter.swap(state, O::RMW);
+    }
+}
+
+#[cfg(all(test, target_has_atomic = "64"))]
+mod atomic_counter {
+    use super::*;
+    use std::sync::atomic::{AtomicI32, AtomicI64};
+
+    #[test]
+    fn fetch_and_reset() {
+        let counter = AtomicCounter::<AtomicI32>::ZERO;
+        counter.add_assign(2);
+        assert_eq!(counter.fetch_and_reset(), Ok(2));
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    fn zero() {
+        let counter = AtomicCounter::<AtomicI32>::ZERO;
+        assert_eq!(counter.fetch(), 0);
+        let counter = AtomicCounter::<AtomicI64>::ZERO;
+        assert_eq!(counter.fetch(), 0);
+        let counter = AtomicCounter::<AtomicUsize>::ZERO;
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = AtomicCounter::<AtomicI64>::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1i64);
+    }
+
+    #[test]
+    fn ordering() {
+        let counter = AtomicCounter::<AtomicI32, ordering::AcqRel>::ZERO;
+        counter.add_assign(2);
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), 1);
+        let counter = AtomicCounter::<AtomicI64, ordering::SeqCst>::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = AtomicCounter::<AtomicI32>::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1i32);
+    }
+
+    #[test]
+    fn wrap() {
+        let counter = AtomicCounter::<AtomicUsize>::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), usize::MAX);
+    }
+}
+
+/// An [`AtomicIsize`] padded and aligned to the cache line size, whose value
+/// never goes below zero.
+///
+/// This behaves like [`RelaxedCounter`], but [`Counter::sub_assign`] clamps
+/// the count at `0` with a compare-and-swap loop. A transient
+/// over-decrement (e.g., from an [`Instance`](crate::Instance) that was
+/// forgotten and then dropped twice) therefore reads as `0` rather than a
+/// confusing negative population. Decrements are somewhat slower than
+/// [`RelaxedCounter`]'s under contention.
+#[repr(transparent)]
+pub struct SaturatingCounter {
+    counter: CachePadded<AtomicIsize>,
+}
+
+impl SaturatingCounter {
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            counter: CachePadded::new(AtomicIsize::new(0)),
+        }
+    }
+}
+
+impl Default for SaturatingCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Counter for SaturatingCounter {
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        let _ = self.counter.fetch_add(n, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        let mut count = self.counter.load(Ordering::Relaxed);
+        loop {
+            let clamped = count.saturating_sub(n).max(0);
+            match self.counter.compare_exchange_weak(
+                count,
+                clamped,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(actual) => count = actual,
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> isize {
+        self.counter.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    fn fetch_and_reset(&self) -> Result<isize, Unsupported> {
+        Ok(self.counter.swap(0, Ordering::Relaxed))
+    }
+}
+
+impl Checkpoint for SaturatingCounter {
+    type State = isize;
+
+    fn checkpoint(&self) -> isize {
+        self.counter.load(Ordering::Relaxed)
+    }
+
+    fn restore(&self, state: isize) {
+        self.counter.store(state.max(0), Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod saturating_counter {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = SaturatingCounter::ZERO;
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = SaturatingCounter::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = SaturatingCounter::ZERO;
+        counter.add_assign(2);
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn saturate() {
+        let counter = SaturatingCounter::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), 0);
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+}
+
+/// A [`Counter`] that panics, in debug builds, when decremented below zero.
+///
+/// This behaves like [`RelaxedCounter`], but with debug assertions enabled,
+/// a decrement that leaves the counter negative panics, pointing at the
+/// mismatched drop of an [`Instance`](crate::Instance). With debug
+/// assertions disabled, the check is compiled out. See
+/// [`DiagnosticCounter`] for a counter that records underflows instead.
+///
+/// ```should_panic
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::CheckedCounter")]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// type_census::counter::Counter::sub_assign(Foo::counter(), 1);
+/// # if !cfg!(debug_assertions) { panic!() }
+/// ```
+#[repr(transparent)]
+pub struct CheckedCounter {
+    counter: CachePadded<AtomicIsize>,
+}
+
+impl CheckedCounter {
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            counter: CachePadded::new(AtomicIsize::new(0)),
+        }
+    }
+
+    #[cold]
+    #[track_caller]
+    fn underflow(value: isize) -> ! {
+        panic!("counter decremented below zero, to {value}")
+    }
+}
+
+impl Default for CheckedCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Counter for CheckedCounter {
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        let _ = self.counter.fetch_add(n, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    #[track_caller]
+    fn sub_assign(&self, n: isize) {
+        let value = self.counter.fetch_sub(n, Ordering::Relaxed).wrapping_sub(n);
+        if cfg!(debug_assertions) && value < 0 {
+            Self::underflow(value);
+        }
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> isize {
+        self.counter.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    fn fetch_and_reset(&self) -> Result<isize, Unsupported> {
+        Ok(self.counter.swap(0, Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod checked_counter {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = CheckedCounter::ZERO;
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = CheckedCounter::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = CheckedCounter::ZERO;
+        counter.add_assign(1);
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "counter decremented below zero, to -1")]
+    fn underflow() {
+        let counter = CheckedCounter::ZERO;
+        counter.sub_assign(1);
+    }
+}
+
+/// A [`Counter`] that buffers updates in thread-local storage, and flushes
+/// them to a shared atomic once every `FLUSH` operations.
+///
+/// Most updates touch only the current thread's buffer, so instances are
+/// nearly free to construct and drop, even in allocation-heavy loops. In
+/// exchange, [`Counter::fetch`] reads only the shared atomic: it lags by up
+/// to `FLUSH - 1` operations per thread. A thread's buffers are flushed
+/// when the thread exits, or when [`BufferedCounter::flush`] is called on
+/// that thread.
+///
+/// The shared atomic is allocated on first use and never freed.
+pub struct BufferedCounter<const FLUSH: usize> {
+    shared: std::sync::OnceLock<&'static CachePadded<AtomicIsize>>,
+}
+
+/// The pending updates of a thread to one [`BufferedCounter`].
+struct Buffer {
+    shared: &'static AtomicIsize,
+    delta: isize,
+    ops: usize,
+}
+
+/// The pending updates of a thread, flushed when the thread exits.
+struct Buffers(Vec<Buffer>);
+
+impl Drop for Buffers {
+    fn drop(&mut self) {
+        for buffer in &self.0 {
+            let _ = buffer.shared.fetch_add(buffer.delta, Ordering::Relaxed);
+        }
+    }
+}
+
+thread_local! {
+    static BUFFERS: std::cell::RefCell<Buffers> =
+        const { std::cell::RefCell::new(Buffers(Vec::new())) };
+}
+
+impl<const FLUSH: usize> BufferedCounter<FLUSH> {
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            shared: std::sync::OnceLock::new(),
+        }
+    }
+
+    fn shared(&self) -> &'static AtomicIsize {
+        self.shared
+            .get_or_init(|| Box::leak(Box::new(CachePadded::new(AtomicIsize::new(0)))))
+    }
+
+    #[inline(always)]
+    fn buffer(&self, n: isize) {
+        let shared = self.shared();
+        let buffered = BUFFERS.try_with(|buffers| {
+            let mut buffers = buffers.borrow_mut();
+            let index = match buffers
+                .0
+                .iter()
+                .position(|buffer| std::ptr::eq(buffer.shared, shared))
+            {
+                Some(index) => index,
+                None => {
+                    buffers.0.push(Buffer {
+                        shared,
+                        delta: 0,
+                        ops: 0,
+                    });
+                    buffers.0.len() - 1
+                }
+            };
+            let buffer = &mut buffers.0[index];
+            buffer.delta = buffer.delta.wrapping_add(n);
+            buffer.ops += 1;
+            if buffer.ops >= FLUSH {
+                let _ = shared.fetch_add(std::mem::take(&mut buffer.delta), Ordering::Relaxed);
+                buffer.ops = 0;
+            }
+        });
+        // The thread is exiting, and its buffers are already flushed.
+        if buffered.is_err() {
+            let _ = shared.fetch_add(n, Ordering::Relaxed);
+        }
+    }
+
+    /// Flushes the current thread's pending updates to this counter.
+    pub fn flush(&self) {
+        let shared = self.shared();
+        let _ = BUFFERS.try_with(|buffers| {
+            let mut buffers = buffers.borrow_mut();
+            if let Some(buffer) = buffers
+                .0
+                .iter_mut()
+                .find(|buffer| std::ptr::eq(buffer.shared, shared))
+            {
+                let _ = shared.fetch_add(std::mem::take(&mut buffer.delta), Ordering::Relaxed);
+                buffer.ops = 0;
+            }
+        });
+    }
+}
+
+impl<const FLUSH: usize> Default for BufferedCounter<FLUSH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const FLUSH: usize> Counter for BufferedCounter<FLUSH> {
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        self.buffer(n);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        self.buffer(n.wrapping_neg());
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> isize {
+        self.shared().load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod buffered_counter {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = BufferedCounter::<4>::ZERO;
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = BufferedCounter::<1>::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = BufferedCounter::<1>::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+    }
+
+    #[test]
+    fn buffered() {
+        let counter = BufferedCounter::<3>::ZERO;
+        counter.add_assign(1);
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 0);
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 3);
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), 3);
+        counter.flush();
+        assert_eq!(counter.fetch(), 2);
+    }
+
+    #[test]
+    fn thread_exit() {
+        static COUNTER: BufferedCounter<100> = BufferedCounter::ZERO;
+        std::thread::spawn(|| COUNTER.add_assign(5)).join().unwrap();
+        assert_eq!(COUNTER.fetch(), 5);
+    }
+}
+
+/// A sharded counter that selects a bucket by the CPU the current thread is
+/// running on.
+///
+/// Like [`DistributedCounter`], this spreads updates over `BUCKETS`
+/// cache-padded atomics, but picks a bucket with [`sched_getcpu`] rather
+/// than by thread. Threads running on the same CPU can't update
+/// concurrently, so with at least as many buckets as CPUs, contention is
+/// rare no matter how many threads there are. A thread may migrate between
+/// reading its CPU and updating the bucket, so updates are still atomic
+/// read-modify-writes, with [`Ordering::Relaxed`].
+///
+/// Only available on Linux, with the `per-cpu` feature.
+///
+/// [`sched_getcpu`]: https://man7.org/linux/man-pages/man3/sched_getcpu.3.html
+#[cfg(all(feature = "per-cpu", target_os = "linux"))]
+pub struct PerCpuCounter<const BUCKETS: usize> {
+    counters: [CachePadded<AtomicIsize>; BUCKETS],
+}
+
+#[cfg(all(feature = "per-cpu", target_os = "linux"))]
+impl<const BUCKETS: usize> PerCpuCounter<BUCKETS> {
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const BUCKET: CachePadded<AtomicIsize> = CachePadded::new(AtomicIsize::new(0));
+        Self {
+            counters: [BUCKET; BUCKETS],
+        }
+    }
+
+    #[inline(always)]
+    fn bucket(&self) -> &AtomicIsize {
+        // SAFETY: `sched_getcpu` has no preconditions.
+        let cpu = unsafe { libc::sched_getcpu() };
+        let cpu = usize::try_from(cpu).unwrap_or(0);
+        &self.counters[cpu % BUCKETS]
+    }
+}
+
+#[cfg(all(feature = "per-cpu", target_os = "linux"))]
+impl<const BUCKETS: usize> Default for PerCpuCounter<BUCKETS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(feature = "per-cpu", target_os = "linux"))]
+impl<const BUCKETS: usize> Counter for PerCpuCounter<BUCKETS> {
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        let _ = self.bucket().fetch_add(n, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        let _ = self.bucket().fetch_sub(n, Ordering::Relaxed);
+    }
+
+    fn fetch(&self) -> isize {
+        self.counters.iter().fold(0isize, |sum, bucket| {
+            sum.wrapping_add(bucket.load(Ordering::Relaxed))
+        })
+    }
+
+    fn fetch_exact(&self) -> isize {
+        sum_exact(|| {
+            self.counters
+                .iter()
+                .map(|bucket| bucket.load(Ordering::Acquire))
+        })
+    }
+}
+
+#[cfg(all(feature = "per-cpu", target_os = "linux"))]
+impl<const BUCKETS: usize> Checkpoint for PerCpuCounter<BUCKETS> {
+    type State = [isize; BUCKETS];
+
+    fn checkpoint(&self) -> [isize; BUCKETS] {
+        let mut state = [0; BUCKETS];
+        for (bucket, counter) in state.iter_mut().zip(&self.counters) {
+            *bucket = counter.load(Ordering::Relaxed);
+        }
+        state
+    }
+
+    fn restore(&self, state: [isize; BUCKETS]) {
+        for (bucket, counter) in state.iter().zip(&self.counters) {
+            counter.store(*bucket, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "per-cpu", target_os = "linux"))]
+mod per_cpu_counter {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = PerCpuCounter::<4>::ZERO;
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = PerCpuCounter::<4>::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = PerCpuCounter::<4>::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+    }
+
+    #[test]
+    fn threads() {
+        let counter = PerCpuCounter::<4>::ZERO;
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..1000 {
+                        counter.add_assign(1);
+                    }
+                });
+            }
+        });
+        assert_eq!(counter.fetch(), 8000);
+    }
+}
+
+/// A [`Counter`] that starts as a single atomic, and shards itself once it
+/// detects contention.
+///
+/// This is the full ["adaptive multi-counter" described by Travis
+/// Downs][multi]. Updates are applied to a single atomic with a
+/// compare-and-swap; each failed compare-and-swap is tallied, and once
+/// [`AdaptiveCounter::CONTENTION_THRESHOLD`] failures have been observed
+/// within one [`AdaptiveCounter::CONTENTION_WINDOW`], the counter allocates
+/// one cache-padded bucket per unit of [available parallelism], and
+/// thereafter spreads updates across them by thread. The tally restarts
+/// with each window, so occasional failures spread over a long run don't
+/// add up to a false alarm. Uncontended counters thus stay small and fast to
+/// read, and contended counters need no hand-tuned bucket count. All
+/// operations use [`Ordering::Relaxed`]; only failed compare-and-swaps read
+/// the time, from `C`.
+///
+/// The buckets, once allocated, are never freed.
+///
+/// [multi]: https://travisdowns.github.io/blog/2020/07/06/concurrency-costs.html#adaptive-multi-counter
+/// [available parallelism]: std::thread::available_parallelism
+pub struct AdaptiveCounter<C = SystemClock> {
+    counter: CachePadded<AtomicIsize>,
+    failures: AtomicUsize,
+    /// The start of the current window, in nanoseconds since `start`.
+    window: AtomicU64,
+    start: std::sync::OnceLock<Instant>,
+    buckets: std::sync::OnceLock<Box<[CachePadded<AtomicIsize>]>>,
+    clock: C,
+}
+
+impl<C> AdaptiveCounter<C>
+where
+    C: ConstClock,
+{
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            counter: CachePadded::new(AtomicIsize::new(0)),
+            failures: AtomicUsize::new(0),
+            window: AtomicU64::new(0),
+            start: std::sync::OnceLock::new(),
+            buckets: std::sync::OnceLock::new(),
+            clock: C::NEW,
+        }
+    }
+
+    /// The number of failed compare-and-swaps, within one
+    /// [`CONTENTION_WINDOW`](Self::CONTENTION_WINDOW), after which the
+    /// counter shards itself.
+    pub const CONTENTION_THRESHOLD: usize = 64;
+
+    /// The period over which failed compare-and-swaps are tallied.
+    pub const CONTENTION_WINDOW: std::time::Duration = std::time::Duration::from_secs(1);
+
+    /// Produces the clock of this counter.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Produces `true` if this counter has sharded itself.
+    pub fn is_sharded(&self) -> bool {
+        self.buckets.get().is_some()
+    }
+
+    #[cold]
+    fn shard(&self) -> &[CachePadded<AtomicIsize>] {
+        self.buckets.get_or_init(|| {
+            let buckets = std::thread::available_parallelism().map_or(1, |n| n.get());
+            (0..buckets)
+                .map(|_| CachePadded::new(AtomicIsize::new(0)))
+                .collect()
+        })
+    }
+
+    /// Tallies a failed compare-and-swap, producing `true` if the tally of
+    /// the current window has reached the threshold.
+    ///
+    /// Failures tallied concurrently with the start of a new window may be
+    /// lost; the threshold is a heuristic.
+    #[cold]
+    fn contended(&self) -> bool {
+        let start = *self.start.get_or_init(|| self.clock.now());
+        let now = self.clock.now().saturating_duration_since(start);
+        let now = u64::try_from(now.as_nanos()).unwrap_or(u64::MAX);
+        let window = self.window.load(Ordering::Relaxed);
+        if now.saturating_sub(window) >= Self::CONTENTION_WINDOW.as_nanos() as u64
+            && self
+                .window
+                .compare_exchange(window, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            self.failures.store(0, Ordering::Relaxed);
+        }
+        self.failures.fetch_add(1, Ordering::Relaxed) + 1 >= Self::CONTENTION_THRESHOLD
+    }
+
+    #[inline(always)]
+    fn add(&self, n: isize) {
+        if let Some(buckets) = self.buckets.get() {
+            let _ = buckets[thread_bucket(buckets.len())].fetch_add(n, Ordering::Relaxed);
+            return;
+        }
+        let mut count = self.counter.load(Ordering::Relaxed);
+        loop {
+            match self.counter.compare_exchange_weak(
+                count,
+                count.wrapping_add(n),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(actual) => count = actual,
+            }
+            if self.contended() {
+                let buckets = self.shard();
+                let _ = buckets[thread_bucket(buckets.len())].fetch_add(n, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+}
+
+impl<C> Default for AdaptiveCounter<C>
+where
+    C: ConstClock,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> Counter for AdaptiveCounter<C>
+where
+    C: ConstClock,
+{
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        self.add(n);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        self.add(n.wrapping_neg());
+    }
+
+    fn fetch(&self) -> isize {
+        let count = self.counter.load(Ordering::Relaxed);
+        match self.buckets.get() {
+            Some(buckets) => buckets.iter().fold(count, |sum, bucket| {
+                sum.wrapping_add(bucket.load(Ordering::Relaxed))
+            }),
+            None => count,
+        }
+    }
+
+    fn fetch_exact(&self) -> isize {
+        sum_exact(|| {
+            std::iter::once(&*self.counter)
+                .chain(
+                    self.buckets
+                        .get()
+                        .into_iter()
+                        .flatten()
+                        .map(|bucket| &**bucket),
+                )
+                .map(|bucket| bucket.load(Ordering::Acquire))
+        })
+    }
+}
+
+#[cfg(test)]
+mod adaptive_counter {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn zero() {
+        let counter = AdaptiveCounter::<SystemClock>::ZERO;
+        assert_eq!(counter.fetch(), 0);
+        assert!(!counter.is_sharded());
+    }
+
+    #[test]
+    fn increment() {
+        let counter = AdaptiveCounter::<SystemClock>::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = AdaptiveCounter::<SystemClock>::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+    }
+
+    #[test]
+    fn sharded() {
+        let counter = AdaptiveCounter::<SystemClock>::ZERO;
+        counter.add_assign(2);
+        counter.shard();
+        assert!(counter.is_sharded());
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn threshold() {
+        let counter = AdaptiveCounter::<MockClock>::ZERO;
+        for _ in 1..AdaptiveCounter::<MockClock>::CONTENTION_THRESHOLD {
+            assert!(!counter.contended());
+        }
+        assert!(counter.contended());
+    }
+
+    #[test]
+    fn decay() {
+        let counter = AdaptiveCounter::<MockClock>::ZERO;
+        for _ in 0..3 {
+            for _ in 1..AdaptiveCounter::<MockClock>::CONTENTION_THRESHOLD {
+                assert!(!counter.contended());
+            }
+            counter
+                .clock()
+                .advance(AdaptiveCounter::<MockClock>::CONTENTION_WINDOW);
+        }
+    }
+
+    #[test]
+    fn threads() {
+        let counter = AdaptiveCounter::<SystemClock>::ZERO;
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..10_000 {
+                        counter.add_assign(1);
+                    }
+                });
+            }
+        });
+        assert_eq!(counter.fetch(), 80_000);
+    }
+}
+
+/// A sharded counter whose buckets are grouped by NUMA node.
+///
+/// Each NUMA node has its own group of buckets, one per CPU of the node,
+/// and each update goes to the bucket of the CPU the current thread is
+/// running on, as reported by [`sched_getcpu`]. Cache lines are thus
+/// contended only by CPUs of the same socket, avoiding the cross-socket
+/// cache-line bouncing that degrades [`DistributedCounter`] on large
+/// multi-socket servers. Updates use [`Ordering::Relaxed`].
+///
+/// The topology is read from `/sys/devices/system/node` when the counter is
+/// first used, into a table mapping each CPU to its node and its index
+/// within the node; if it can't be read, the counter has a single bucket.
+/// CPUs missing from the table, such as those hotplugged later, share the
+/// buckets of the first node. The buckets are never freed.
+///
+/// Only available on Linux, with the `numa` feature.
+///
+/// [`sched_getcpu`]: https://man7.org/linux/man-pages/man3/sched_getcpu.3.html
+#[cfg(all(feature = "numa", target_os = "linux"))]
+pub struct NumaCounter {
+    topology: std::sync::OnceLock<Topology>,
+}
+
+/// The buckets of one NUMA node, one per CPU of the node.
+#[cfg(all(feature = "numa", target_os = "linux"))]
+type Node = Box<[CachePadded<AtomicIsize>]>;
+
+/// The buckets of a [`NumaCounter`], by node, and the location of each CPU's
+/// bucket.
+#[cfg(all(feature = "numa", target_os = "linux"))]
+struct Topology {
+    nodes: Box<[Node]>,
+    /// The index of the node of each CPU in `nodes`, and the index of the
+    /// CPU's bucket within its node, by CPU number.
+    cpus: Box<[(usize, usize)]>,
+}
+
+#[cfg(all(feature = "numa", target_os = "linux"))]
+impl Topology {
+    /// Reads the topology of this machine from sysfs.
+    fn read() -> Self {
+        let read = |path: &str| std::fs::read_to_string(path).ok();
+        let cpus_of = |node: usize| -> Vec<usize> {
+            read(&format!("/sys/devices/system/node/node{node}/cpulist"))
+                .map(|list| parse_cpu_list(&list).collect())
+                .unwrap_or_default()
+        };
+        let nodes: Vec<Vec<usize>> = read("/sys/devices/system/node/possible")
+            .map(|list| parse_cpu_list(&list).map(cpus_of).collect())
+            .unwrap_or_default();
+        Self::new(nodes)
+    }
+
+    /// Lays out one bucket for each CPU of each of `nodes`.
+    fn new(mut nodes: Vec<Vec<usize>>) -> Self {
+        if nodes.is_empty() {
+            nodes.push(Vec::new());
+        }
+        let len = nodes.iter().flatten().max().map_or(0, |&max| max + 1);
+        let mut cpus = vec![(0, 0); len].into_boxed_slice();
+        for (node, members) in nodes.iter().enumerate() {
+            for (index, &cpu) in members.iter().enumerate() {
+                cpus[cpu] = (node, index);
+            }
+        }
+        let nodes = nodes
+            .iter()
+            .map(|members| {
+                (0..members.len().max(1))
+                    .map(|_| CachePadded::new(AtomicIsize::new(0)))
+                    .collect()
+            })
+            .collect();
+        Self { nodes, cpus }
+    }
+
+    /// Produces the bucket of `cpu`.
+    #[inline(always)]
+    fn bucket(&self, cpu: usize) -> &AtomicIsize {
+        match self.cpus.get(cpu) {
+            Some(&(node, index)) => &self.nodes[node][index],
+            None => {
+                let buckets = &self.nodes[0];
+                &buckets[cpu % buckets.len()]
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "numa", target_os = "linux"))]
+impl NumaCounter {
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            topology: std::sync::OnceLock::new(),
+        }
+    }
+
+    fn topology(&self) -> &Topology {
+        self.topology.get_or_init(Topology::read)
+    }
+
+    fn nodes(&self) -> &[Node] {
+        &self.topology().nodes
+    }
+
+    #[inline(always)]
+    fn bucket(&self) -> &AtomicIsize {
+        // SAFETY: `sched_getcpu` has no preconditions.
+        let cpu = unsafe { libc::sched_getcpu() };
+        self.topology().bucket(usize::try_from(cpu).unwrap_or(0))
+    }
+
+    /// Eventually retrieve the sum of each NUMA node's buckets, in node
+    /// order.
+    pub fn per_node(&self) -> Vec<isize> {
+        self.nodes()
+            .iter()
+            .map(|buckets| {
+                buckets.iter().fold(0isize, |sum, bucket| {
+                    sum.wrapping_add(bucket.load(Ordering::Relaxed))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Parses a Linux CPU or node list, such as `0-3,8-11`, into its members.
+#[cfg(all(feature = "numa", target_os = "linux"))]
+fn parse_cpu_list(list: &str) -> impl Iterator<Item = usize> + '_ {
+    list.trim()
+        .split(',')
+        .filter(|range| !range.is_empty())
+        .filter_map(|range| match range.split_once('-') {
+            Some((start, end)) => Some(start.parse().ok()?..=end.parse().ok()?),
+            None => {
+                let cpu = range.parse().ok()?;
+                Some(cpu..=cpu)
+            }
+        })
+        .flatten()
+}
+
+#[cfg(all(feature = "numa", target_os = "linux"))]
+impl Default for NumaCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(feature = "numa", target_os = "linux"))]
+impl Counter for NumaCounter {
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        let _ = self.bucket().fetch_add(n, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        let _ = self.bucket().fetch_sub(n, Ordering::Relaxed);
+    }
+
+    fn fetch(&self) -> isize {
+        self.per_node()
+            .into_iter()
+            .fold(0isize, |sum, node| sum.wrapping_add(node))
+    }
+
+    fn fetch_exact(&self) -> isize {
+        sum_exact(|| {
+            self.nodes()
+                .iter()
+                .flatten()
+                .map(|bucket| bucket.load(Ordering::Acquire))
+        })
+    }
+}
+
+#[cfg(all(test, feature = "numa", target_os = "linux"))]
+mod numa_counter {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = NumaCounter::ZERO;
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = NumaCounter::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+        assert_eq!(counter.per_node().iter().sum::<isize>(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = NumaCounter::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+    }
+
+    #[test]
+    fn topology() {
+        // two nodes with non-contiguous CPUs, as after CPU hotplug
+        let topology = Topology::new(vec![vec![0, 2], vec![1, 3, 4]]);
+        assert_eq!(topology.nodes.len(), 2);
+        assert_eq!(topology.nodes[0].len(), 2);
+        assert_eq!(topology.nodes[1].len(), 3);
+        assert_eq!(&*topology.cpus, [(0, 0), (1, 0), (0, 1), (1, 1), (1, 2)]);
+        assert!(std::ptr::eq(topology.bucket(4), &*topology.nodes[1][2]));
+        assert!(std::ptr::eq(topology.bucket(7), &*topology.nodes[0][1]));
+    }
+
+    #[test]
+    fn no_topology() {
+        let topology = Topology::new(Vec::new());
+        assert_eq!(topology.nodes.len(), 1);
+        assert_eq!(topology.nodes[0].len(), 1);
+        assert!(std::ptr::eq(topology.bucket(3), &*topology.nodes[0][0]));
+    }
+
+    #[test]
+    fn cpu_list() {
+        assert_eq!(parse_cpu_list("0\n").collect::<Vec<_>>(), [0]);
+        assert_eq!(
+            parse_cpu_list("0-2,8,10-11\n").collect::<Vec<_>>(),
+            [0, 1, 2, 8, 10, 11]
+        );
+        assert_eq!(parse_cpu_list("\n").count(), 0);
+    }
+}
+
+/// A [`Counter`] that also tracks the highest value it has ever held.
+///
+/// This behaves like [`RelaxedCounter`], but each increment that raises the
+/// counter above its previous peak also raises the peak, retrievable with
+/// [`PeakCounter::peak`]. This answers "what was the worst case?" for
+/// capacity planning. An increment costs a second atomic operation only
+/// when it sets a new peak.
+///
+/// ```
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Clone, Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::PeakCounter")]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// let foos = vec![Foo { _instance: Instance::new() }; 3];
+/// drop(foos);
+///
+/// assert_eq!(Foo::instances(), 0);
+/// assert_eq!(Foo::counter().peak(), 3);
+/// ```
+pub struct PeakCounter {
+    counter: CachePadded<Peak>,
+}
+
+struct Peak {
+    live: AtomicIsize,
+    peak: AtomicIsize,
+}
+
+impl PeakCounter {
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            counter: CachePadded::new(Peak {
+                live: AtomicIsize::new(0),
+                peak: AtomicIsize::new(0),
+            }),
+        }
+    }
+
+    /// Eventually retrieve the highest value this counter has held.
+    #[inline(always)]
+    pub fn peak(&self) -> isize {
+        self.counter.peak.load(Ordering::Relaxed)
+    }
+
+    /// Resets the peak of this counter to its current value, producing the
+    /// previous peak.
+    ///
+    /// This lets the peak be reported per interval.
+    pub fn reset_peak(&self) -> isize {
+        self.counter
+            .peak
+            .swap(self.counter.live.load(Ordering::Relaxed), Ordering::Relaxed)
+    }
+}
+
+/// The values carried by a [`PeakCounter`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PeakStats {
+    /// The current value of the counter.
+    pub live: isize,
+    /// The highest value the counter has held.
+    pub peak: isize,
+}
+
+impl FetchAll for PeakCounter {
+    type Stats = PeakStats;
+
+    /// Retrieves the current value and the peak of this counter.
+    ///
+    /// The current value is read first; the peak is read afterwards, and
+    /// is at least the current value.
+    #[inline(always)]
+    fn fetch_all(&self) -> PeakStats {
+        let live = self.counter.live.load(Ordering::Relaxed);
+        let peak = self.counter.peak.load(Ordering::Relaxed).max(live);
+        PeakStats { live, peak }
+    }
+}
+
+impl Default for PeakCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Counter for PeakCounter {
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        let live = self
+            .counter
+            .live
+            .fetch_add(n, Ordering::Relaxed)
+            .wrapping_add(n);
+        if live > self.counter.peak.load(Ordering::Relaxed) {
+            let _ = self.counter.peak.fetch_max(live, Ordering::Relaxed);
+        }
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        let _ = self.counter.live.fetch_sub(n, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> isize {
+        self.counter.live.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod peak_counter {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = PeakCounter::ZERO;
+        assert_eq!(counter.fetch(), 0);
+        assert_eq!(counter.peak(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = PeakCounter::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+        assert_eq!(counter.peak(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = PeakCounter::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+        assert_eq!(counter.peak(), 0);
+    }
+
+    #[test]
+    fn peak() {
+        let counter = PeakCounter::ZERO;
+        counter.add_assign(3);
+        counter.sub_assign(2);
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 2);
+        assert_eq!(counter.peak(), 3);
+        assert_eq!(counter.reset_peak(), 3);
+        assert_eq!(counter.peak(), 2);
+    }
+
+    #[test]
+    fn fetch_all() {
+        let counter = PeakCounter::ZERO;
+        counter.add_assign(3);
+        counter.sub_assign(2);
+        assert_eq!(counter.fetch_all(), PeakStats { live: 1, peak: 3 });
+    }
+}
+
+/// A [`Counter`] that separately tracks the total number of increments and
+/// decrements ever applied to it.
+///
+/// The two totals never reset, so churn ("we created 40M request objects
+/// this hour") is observable by differencing [`CumulativeCounter::created`]
+/// or [`CumulativeCounter::dropped`] over time, in addition to the extant
+/// count. Each update is a single [`Ordering::Relaxed`] atomic operation on
+/// one of the two totals; [`Counter::fetch`] is their difference.
+///
+/// ```
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Clone, Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::CumulativeCounter")]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// let foos = vec![Foo { _instance: Instance::new() }; 3];
+/// drop(foos);
+/// let _foo = Foo { _instance: Instance::new() };
+///
+/// assert_eq!(Foo::instances(), 1);
+/// assert_eq!(Foo::counter().created(), 4);
+/// assert_eq!(Foo::counter().dropped(), 3);
+/// ```
+pub struct CumulativeCounter {
+    counter: CachePadded<Cumulative>,
+}
+
+struct Cumulative {
+    created: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl CumulativeCounter {
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            counter: CachePadded::new(Cumulative {
+                created: AtomicU64::new(0),
+                dropped: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Eventually retrieve the total number of increments applied to this
+    /// counter.
+    #[inline(always)]
+    pub fn created(&self) -> u64 {
+        self.counter.created.load(Ordering::Relaxed)
+    }
+
+    /// Eventually retrieve the total number of decrements applied to this
+    /// counter.
+    #[inline(always)]
+    pub fn dropped(&self) -> u64 {
+        self.counter.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// The values carried by a [`CumulativeCounter`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CumulativeStats {
+    /// The current value of the counter.
+    pub live: isize,
+    /// The total number of increments applied to the counter.
+    pub created: u64,
+    /// The total number of decrements applied to the counter.
+    pub dropped: u64,
+}
+
+impl FetchAll for CumulativeCounter {
+    type Stats = CumulativeStats;
+
+    /// Retrieves the totals of this counter, and their difference.
+    ///
+    /// As with [`Counter::fetch`], the decrements are read first, so the
+    /// count is never lower than it ever was.
+    #[inline(always)]
+    fn fetch_all(&self) -> CumulativeStats {
+        let dropped = self.dropped();
+        let created = self.created();
+        CumulativeStats {
+            live: created.wrapping_sub(dropped) as isize,
+            created,
+            dropped,
+        }
+    }
+}
+
+impl Default for CumulativeCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Counter for CumulativeCounter {
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        let _ = self.counter.created.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        let _ = self.counter.dropped.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    /// Produces the difference of the totals.
+    ///
+    /// The decrements are read first, so that a concurrent creation and
+    /// drop can't make the count appear lower than it ever was.
+    #[inline(always)]
+    fn fetch(&self) -> isize {
+        let dropped = self.dropped();
+        let created = self.created();
+        created.wrapping_sub(dropped) as isize
+    }
+}
+
+impl Checkpoint for CumulativeCounter {
+    type State = (u64, u64);
+
+    fn checkpoint(&self) -> (u64, u64) {
+        let dropped = self.dropped();
+        (self.created(), dropped)
+    }
+
+    fn restore(&self, (created, dropped): (u64, u64)) {
+        self.counter.created.store(created, Ordering::Relaxed);
+        self.counter.dropped.store(dropped, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod cumulative_counter {
+    use super::*;
+
+    #[test]
+    fn checkpoint() {
+        let counter = CumulativeCounter::ZERO;
+        counter.add_assign(2);
+        let state = counter.checkpoint();
+        counter.sub_assign(5);
+        counter.restore(state);
+        assert_eq!(counter.fetch(), 2);
+        assert_eq!(counter.dropped(), 0);
+    }
+
+    #[test]
+    fn zero() {
+        let counter = CumulativeCounter::ZERO;
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = CumulativeCounter::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+        assert_eq!(counter.created(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = CumulativeCounter::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+        assert_eq!(counter.dropped(), 1);
+    }
+
+    #[test]
+    fn churn() {
+        let counter = CumulativeCounter::ZERO;
+        for _ in 0..5 {
+            counter.add_assign(1);
+            counter.sub_assign(1);
+        }
+        assert_eq!(counter.fetch(), 0);
+        assert_eq!(counter.created(), 5);
+        assert_eq!(counter.dropped(), 5);
+    }
+
+    #[test]
+    fn fetch_all() {
+        let counter = CumulativeCounter::ZERO;
+        counter.add_assign(3);
+        counter.sub_assign(2);
+        assert_eq!(
+            counter.fetch_all(),
+            CumulativeStats {
+                live: 1,
+                created: 3,
+                dropped: 2,
+            }
+        );
+    }
+}
+
+/// A [`Counter`] that tracks the extant, peak, total created and total
+/// dropped counts of a type, in a single padded struct.
+///
+/// The totals are kept in separate [`AtomicU64`]s, so an increment costs one
+/// atomic operation and one load (plus one more, when it sets a new peak),
+/// and a decrement costs one; the extant count is derived from the totals,
+/// so it is always consistent with them. All operations use
+/// [`Ordering::Relaxed`]. The totals only wrap around after [`u64::MAX`]
+/// operations. Retrieve everything at once with [`FetchAll::fetch_all`].
+///
+/// This counter is only available on targets with 64-bit atomics.
+///
+/// ```
+/// use type_census::{Instance, Tabulate};
+/// use type_census::counter::{FetchAll, Statistics};
+///
+/// #[derive(Clone, Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::StatsCounter")]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// let foos = vec![Foo { _instance: Instance::new() }; 3];
+/// drop(foos);
+/// let _foo = Foo { _instance: Instance::new() };
+///
+/// assert_eq!(
+///     Foo::counter().fetch_all(),
+///     Statistics { live: 1, peak: 3, created: 4, dropped: 3 }
+/// );
+/// ```
+#[cfg(target_has_atomic = "64")]
+pub struct StatsCounter {
+    counter: CachePadded<Stats>,
+}
+
+#[cfg(target_has_atomic = "64")]
+struct Stats {
+    created: AtomicU64,
+    dropped: AtomicU64,
+    peak: AtomicIsize,
+}
+
+/// The values tracked by a [`StatsCounter`].
+#[cfg(target_has_atomic = "64")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statistics {
+    /// The current value of the counter.
+    pub live: isize,
+    /// The highest value the counter has held.
+    pub peak: isize,
+    /// The total number of increments applied to the counter.
+    pub created: u64,
+    /// The total number of decrements applied to the counter.
+    pub dropped: u64,
+}
+
+#[cfg(target_has_atomic = "64")]
+impl StatsCounter {
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            counter: CachePadded::new(Stats {
+                created: AtomicU64::new(0),
+                dropped: AtomicU64::new(0),
+                peak: AtomicIsize::new(0),
+            }),
+        }
+    }
+
+    /// Eventually retrieve every value tracked by this counter.
+    ///
+    /// This is [`FetchAll::fetch_all`].
+    pub fn stats(&self) -> Statistics {
+        self.fetch_all()
+    }
+
+    /// Derives the extant count from the totals.
+    #[inline(always)]
+    fn live(created: u64, dropped: u64) -> isize {
+        created.wrapping_sub(dropped) as isize
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl Default for StatsCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl Counter for StatsCounter {
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        let created = self
+            .counter
+            .created
+            .fetch_add(n as u64, Ordering::Relaxed)
+            .wrapping_add(n as u64);
+        let live = Self::live(created, self.counter.dropped.load(Ordering::Relaxed));
+        if live > self.counter.peak.load(Ordering::Relaxed) {
+            let _ = self.counter.peak.fetch_max(live, Ordering::Relaxed);
+        }
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        let _ = self.counter.dropped.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> isize {
+        self.fetch_all().live
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl FetchAll for StatsCounter {
+    type Stats = Statistics;
+
+    /// Retrieves every value tracked by this counter.
+    ///
+    /// The totals are read one after the other, so under concurrent updates
+    /// they may not correspond to a single instant; the extant count is
+    /// always their difference. The peak is read afterwards, and is at least
+    /// the extant count.
+    #[inline(always)]
+    fn fetch_all(&self) -> Statistics {
+        let dropped = self.counter.dropped.load(Ordering::Relaxed);
+        let created = self.counter.created.load(Ordering::Relaxed);
+        let live = Self::live(created, dropped);
+        let peak = self.counter.peak.load(Ordering::Relaxed).max(live);
+        Statistics {
+            live,
+            peak,
+            created,
+            dropped,
+        }
+    }
+}
+
+#[cfg(all(test, target_has_atomic = "64"))]
+mod stats_counter {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = StatsCounter::ZERO;
+        assert_eq!(counter.fetch(), 0);
+        assert_eq!(counter.stats(), Statistics::default());
+    }
+
+    #[test]
+    fn increment() {
+        let counter = StatsCounter::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = StatsCounter::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+        assert_eq!(counter.stats().dropped, 1);
+    }
+
+    #[test]
+    fn stats() {
+        let counter = StatsCounter::ZERO;
+        counter.add_assign(3);
+        counter.sub_assign(2);
+        counter.add_assign(1);
+        assert_eq!(
+            counter.stats(),
+            Statistics {
+                live: 2,
+                peak: 3,
+                created: 4,
+                dropped: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn beyond_u32() {
+        let counter = StatsCounter::ZERO;
+        counter.add_assign(u32::MAX as isize);
+        counter.add_assign(2);
+        counter.sub_assign(u32::MAX as isize);
+        assert_eq!(
+            counter.stats(),
+            Statistics {
+                live: 2,
+                peak: u32::MAX as isize + 2,
+                created: u32::MAX as u64 + 2,
+                dropped: u32::MAX as u64,
+            }
+        );
+    }
+
+    #[test]
+    fn consistent() {
+        let counter = StatsCounter::ZERO;
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..1000 {
+                        counter.add_assign(1);
+                        let stats = counter.fetch_all();
+                        assert_eq!(stats.created.wrapping_sub(stats.dropped), stats.live as u64);
+                        assert!(stats.peak >= stats.live);
+                        counter.sub_assign(1);
+                    }
+                });
+            }
+        });
+        let stats = counter.fetch_all();
+        assert_eq!((stats.live, stats.created, stats.dropped), (0, 4000, 4000));
+    }
+}
+
+/// A [`Counter`] that maintains an exponentially weighted moving average of
+/// its creation rate.
+///
+/// [`EwmaCounter::rate_per_sec`] produces the average number of increments
+/// per second, weighting recent time more heavily: the weight of a moment
+/// halves every `HALF_LIFE_SECS` seconds. The average is brought up to date
+/// on each call, assuming a constant rate since the previous call, so
+/// dashboards can chart a type's allocation rate without external sampling.
+/// Time is read from `C`; each update costs two [`Ordering::Relaxed`] atomic
+/// operations, and the first update also reads the clock.
+///
+/// ```
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::EwmaCounter")]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// let _foo = Foo { _instance: Instance::new() };
+/// println!("{:.1} Foo/s", Foo::counter().rate_per_sec());
+/// ```
+pub struct EwmaCounter<const HALF_LIFE_SECS: u64 = 60, C = SystemClock> {
+    counter: CachePadded<Churn>,
+    start: std::sync::OnceLock<Instant>,
+    ewma: Mutex<Ewma>,
+    clock: C,
+}
+
+struct Churn {
+    live: AtomicIsize,
+    created: AtomicU64,
+}
+
+/// The moving average of an [`EwmaCounter`], as of its last update.
+struct Ewma {
+    at: Option<Instant>,
+    created: u64,
+    rate: f64,
+}
+
+impl<const HALF_LIFE_SECS: u64, C> EwmaCounter<HALF_LIFE_SECS, C>
+where
+    C: ConstClock,
+{
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            counter: CachePadded::new(Churn {
+                live: AtomicIsize::new(0),
+                created: AtomicU64::new(0),
+            }),
+            start: std::sync::OnceLock::new(),
+            ewma: Mutex::new(Ewma {
+                at: None,
+                created: 0,
+                rate: 0.0,
+            }),
+            clock: C::NEW,
+        }
+    }
+
+    /// Produces the clock of this counter.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Produces the moving average of the number of increments per second.
+    pub fn rate_per_sec(&self) -> f64 {
+        let Some(&start) = self.start.get() else {
+            return 0.0;
+        };
+        let now = self.clock.now();
+        let created = self.counter.created.load(Ordering::Relaxed);
+        let mut ewma = self.ewma.lock().unwrap_or_else(PoisonError::into_inner);
+        let at = *ewma.at.get_or_insert(start);
+        let elapsed = now.saturating_duration_since(at).as_secs_f64();
+        if elapsed > 0.0 {
+            let rate = created.wrapping_sub(ewma.created) as f64 / elapsed;
+            let alpha = 1.0 - 0.5f64.powf(elapsed / HALF_LIFE_SECS as f64);
+            ewma.rate += alpha * (rate - ewma.rate);
+            ewma.at = Some(now);
+            ewma.created = created;
+        }
+        ewma.rate
+    }
+}
+
+impl<const HALF_LIFE_SECS: u64, C> Default for EwmaCounter<HALF_LIFE_SECS, C>
+where
+    C: ConstClock,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const HALF_LIFE_SECS: u64, C> Counter for EwmaCounter<HALF_LIFE_SECS, C>
+where
+    C: ConstClock,
+{
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        let _ = self.start.get_or_init(|| self.clock.now());
+        let _ = self.counter.live.fetch_add(n, Ordering::Relaxed);
+        let _ = self.counter.created.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        let _ = self.counter.live.fetch_sub(n, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> isize {
+        self.counter.live.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod ewma_counter {
+    use super::*;
+    use crate::clock::MockClock;
+    use std::time::Duration;
+
+    #[test]
+    fn zero() {
+        let counter = EwmaCounter::<60, MockClock>::ZERO;
+        assert_eq!(counter.fetch(), 0);
+        assert_eq!(counter.rate_per_sec(), 0.0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = EwmaCounter::<60, MockClock>::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = EwmaCounter::<60, MockClock>::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+    }
+
+    #[test]
+    fn rate() {
+        let counter = EwmaCounter::<1, MockClock>::ZERO;
+        counter.add_assign(10);
+        counter.clock().advance(Duration::from_secs(1));
+        // Half of the weight is on the last second, at 10/s.
+        assert_eq!(counter.rate_per_sec(), 5.0);
+        counter.clock().advance(Duration::from_secs(1));
+        assert_eq!(counter.rate_per_sec(), 2.5);
+        counter.add_assign(40);
+        counter.clock().advance(Duration::from_secs(2));
+        // Three quarters of the weight is on the last two seconds, at 20/s.
+        assert_eq!(counter.rate_per_sec(), 15.625);
+    }
+}
+
+/// A [`Counter`] that records creations and drops in one-second slots, over
+/// a sliding window of the last `SLOTS` seconds.
+///
+/// [`WindowCounter::rate_per_sec`] and [`WindowCounter::drop_rate_per_sec`]
+/// produce the average rates over the window, which reveal bursts that an
+/// instantaneous count hides. Each slot packs its second and its tally into
+/// one [`AtomicU64`], so a slot is recycled for a new second without losing
+/// concurrent updates. Time is read from `C` on every update.
+///
+/// ```
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::WindowCounter")]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// let _foo = Foo { _instance: Instance::new() };
+/// println!("{:.1} Foo/s", Foo::counter().rate_per_sec());
+/// ```
+pub struct WindowCounter<const SLOTS: usize = 60, C = SystemClock> {
+    live: CachePadded<AtomicIsize>,
+    start: std::sync::OnceLock<Instant>,
+    created: [Slot; SLOTS],
+    dropped: [Slot; SLOTS],
+    clock: C,
+}
+
+/// The tally of one second of a [`WindowCounter`], packed as
+/// `second * 2^32 + tally`.
+struct Slot(AtomicU64);
+
+impl Slot {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: Self = Slot(AtomicU64::new(0));
+
+    #[inline(always)]
+    fn add(&self, second: u32, n: u32) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |packed| {
+                let tally = if (packed >> 32) as u32 == second {
+                    (packed as u32).wrapping_add(n)
+                } else {
+                    n
+                };
+                Some(u64::from(second) << 32 | u64::from(tally))
+            });
+    }
+
+    /// Produces the tally of this slot, if it was last updated in `seconds`.
+    fn tally(&self, seconds: std::ops::RangeInclusive<u32>) -> u32 {
+        let packed = self.0.load(Ordering::Relaxed);
+        if seconds.contains(&((packed >> 32) as u32)) {
+            packed as u32
+        } else {
+            0
+        }
+    }
+}
+
+impl<const SLOTS: usize, C> WindowCounter<SLOTS, C>
+where
+    C: ConstClock,
+{
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            live: CachePadded::new(AtomicIsize::new(0)),
+            start: std::sync::OnceLock::new(),
+            created: [Slot::ZERO; SLOTS],
+            dropped: [Slot::ZERO; SLOTS],
+            clock: C::NEW,
+        }
+    }
+
+    /// Produces the clock of this counter.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Produces the number of whole seconds since this counter was first
+    /// updated.
+    fn second(&self) -> u32 {
+        let start = *self.start.get_or_init(|| self.clock.now());
+        let elapsed = self.clock.now().saturating_duration_since(start);
+        u32::try_from(elapsed.as_secs()).unwrap_or(u32::MAX)
+    }
+
+    #[inline(always)]
+    fn record(slots: &[Slot; SLOTS], second: u32, n: isize) {
+        if SLOTS > 0 {
+            slots[second as usize % SLOTS].add(second, n as u32);
+        }
+    }
+
+    fn rate(&self, slots: &[Slot; SLOTS]) -> f64 {
+        if self.start.get().is_none() || SLOTS == 0 {
+            return 0.0;
+        }
+        let now = self.second();
+        let window = (now as usize + 1).min(SLOTS);
+        let seconds = (now + 1 - window as u32)..=now;
+        let total: u64 = slots
+            .iter()
+            .map(|slot| u64::from(slot.tally(seconds.clone())))
+            .sum();
+        total as f64 / window as f64
+    }
+
+    /// Produces the average number of increments per second over the
+    /// window, or over the time since this counter was first updated, if
+    /// that is shorter.
+    pub fn rate_per_sec(&self) -> f64 {
+        self.rate(&self.created)
+    }
+
+    /// Produces the average number of decrements per second over the
+    /// window, or over the time since this counter was first updated, if
+    /// that is shorter.
+    pub fn drop_rate_per_sec(&self) -> f64 {
+        self.rate(&self.dropped)
+    }
+}
+
+impl<const SLOTS: usize, C> Default for WindowCounter<SLOTS, C>
+where
+    C: ConstClock,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const SLOTS: usize, C> Counter for WindowCounter<SLOTS, C>
+where
+    C: ConstClock,
+{
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        let _ = self.live.fetch_add(n, Ordering::Relaxed);
+        Self::record(&self.created, self.second(), n);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        let _ = self.live.fetch_sub(n, Ordering::Relaxed);
+        Self::record(&self.dropped, self.second(), n);
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> isize {
+        self.live.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod window_counter {
+    use super::*;
+    use crate::clock::MockClock;
+    use std::time::Duration;
+
+    #[test]
+    fn zero() {
+        let counter = WindowCounter::<4, MockClock>::ZERO;
+        assert_eq!(counter.fetch(), 0);
+        assert_eq!(counter.rate_per_sec(), 0.0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = WindowCounter::<4, MockClock>::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+        assert_eq!(counter.rate_per_sec(), 1.0);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = WindowCounter::<4, MockClock>::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+        assert_eq!(counter.drop_rate_per_sec(), 1.0);
+    }
+
+    #[test]
+    fn window() {
+        let counter = WindowCounter::<4, MockClock>::ZERO;
+        counter.add_assign(8);
+        counter.clock().advance(Duration::from_secs(1));
+        counter.add_assign(4);
+        assert_eq!(counter.rate_per_sec(), 6.0);
+        counter.clock().advance(Duration::from_secs(2));
+        assert_eq!(counter.rate_per_sec(), 3.0);
+        // The first second leaves the window, and its slot is recycled.
+        counter.clock().advance(Duration::from_secs(1));
+        counter.add_assign(4);
+        assert_eq!(counter.rate_per_sec(), 2.0);
+        counter.clock().advance(Duration::from_secs(10));
+        assert_eq!(counter.rate_per_sec(), 0.0);
+        assert_eq!(counter.fetch(), 16);
+    }
+}
+
+/// A [`Counter`] that periodically records its own value, with a timestamp,
+/// into a ring buffer of the last `LEN` samples.
+///
+/// A sample is taken by the first update (or call to
+/// [`HistoryCounter::history`]) at least `INTERVAL_MS` milliseconds after
+/// the previous sample, so short-term trends are available without running
+/// a separate sampler. A counter that is neither updated nor queried takes
+/// no samples. Time is read from `C` on every update.
+///
+/// ```
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::HistoryCounter")]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// let _foo = Foo { _instance: Instance::new() };
+/// for (instant, count) in Foo::counter().history() {
+///     println!("{instant:?}: {count}");
+/// }
+/// ```
+pub struct HistoryCounter<const LEN: usize = 60, const INTERVAL_MS: u64 = 1000, C = SystemClock> {
+    live: CachePadded<AtomicIsize>,
+    start: std::sync::OnceLock<Instant>,
+    /// The time, in nanoseconds after `start`, at which the next sample is
+    /// due.
+    due: AtomicU64,
+    ring: Mutex<Ring<LEN>>,
+    clock: C,
+}
+
+struct Ring<const LEN: usize> {
+    samples: [Option<(Instant, isize)>; LEN],
+    /// The index of the oldest sample.
+    next: usize,
+}
+
+impl<const LEN: usize, const INTERVAL_MS: u64, C> HistoryCounter<LEN, INTERVAL_MS, C>
+where
+    C: ConstClock,
+{
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            live: CachePadded::new(AtomicIsize::new(0)),
+            start: std::sync::OnceLock::new(),
+            due: AtomicU64::new(0),
+            ring: Mutex::new(Ring {
+                samples: [None; LEN],
+                next: 0,
+            }),
+            clock: C::NEW,
+        }
+    }
+
+    /// Produces the clock of this counter.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Records a sample, if one is due.
+    #[inline(always)]
+    fn tick(&self) {
+        let now = self.clock.now();
+        let start = *self.start.get_or_init(|| now);
+        let elapsed =
+            u64::try_from(now.saturating_duration_since(start).as_nanos()).unwrap_or(u64::MAX);
+        if elapsed >= self.due.load(Ordering::Relaxed) {
+            self.sample(now, elapsed);
+        }
+    }
+
+    #[cold]
+    fn sample(&self, now: Instant, elapsed: u64) {
+        // Another thread is taking this sample.
+        let Ok(mut ring) = self.ring.try_lock() else {
+            return;
+        };
+        if elapsed < self.due.load(Ordering::Relaxed) || LEN == 0 {
+            return;
+        }
+        let interval = INTERVAL_MS.saturating_mul(1_000_000).max(1);
+        self.due
+            .store(elapsed - elapsed % interval + interval, Ordering::Relaxed);
+        let next = ring.next;
+        ring.samples[next] = Some((now, self.live.load(Ordering::Relaxed)));
+        ring.next = (next + 1) % LEN;
+    }
+
+    /// Produces the recorded samples, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = (Instant, isize)> {
+        if self.start.get().is_some() {
+            self.tick();
+        }
+        let ring = self.ring.lock().unwrap_or_else(PoisonError::into_inner);
+        let (newer, older) = ring.samples.split_at(ring.next);
+        older
+            .iter()
+            .chain(newer)
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<const LEN: usize, const INTERVAL_MS: u64, C> Default for HistoryCounter<LEN, INTERVAL_MS, C>
+where
+    C: ConstClock,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const LEN: usize, const INTERVAL_MS: u64, C> Counter for HistoryCounter<LEN, INTERVAL_MS, C>
+where
+    C: ConstClock,
+{
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        let _ = self.live.fetch_add(n, Ordering::Relaxed);
+        self.tick();
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        let _ = self.live.fetch_sub(n, Ordering::Relaxed);
+        self.tick();
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> isize {
+        self.live.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod history_counter {
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+    use std::time::Duration;
+
+    #[test]
+    fn zero() {
+        let counter = HistoryCounter::<4, 1000, MockClock>::ZERO;
+        assert_eq!(counter.fetch(), 0);
+        assert_eq!(counter.history().count(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = HistoryCounter::<4, 1000, MockClock>::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = HistoryCounter::<4, 1000, MockClock>::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+    }
+
+    #[test]
+    fn history() {
+        let counter = HistoryCounter::<3, 1000, MockClock>::ZERO;
+        let start = counter.clock().now();
+        let second = |n| start + Duration::from_secs(n);
+        counter.add_assign(1);
+        counter.add_assign(1);
+        assert_eq!(counter.history().collect::<Vec<_>>(), [(second(0), 1)]);
+        counter.clock().advance(Duration::from_millis(1500));
+        counter.add_assign(1);
+        counter.clock().advance(Duration::from_millis(1000));
+        counter.sub_assign(3);
+        counter.clock().advance(Duration::from_millis(1000));
+        assert_eq!(
+            counter
+                .history()
+                .map(|(instant, count)| (instant - start, count))
+                .collect::<Vec<_>>(),
+            [
+                (Duration::from_millis(1500), 3),
+                (Duration::from_millis(2500), 0),
+                (Duration::from_millis(3500), 0),
+            ]
+        );
+    }
+}
+
+/// A [`Counter`] that tracks a byte total alongside the instance count.
+///
+/// Each increment of `n` instances also adds `n * SIZE` bytes, and each
+/// decrement subtracts them, so with `SIZE` set to the size of the type,
+/// [`BytesCounter::bytes`] is the memory occupied by its extant instances.
+/// Memory owned indirectly (e.g., the heap buffer of a `Vec`) can be added
+/// and subtracted explicitly with [`BytesCounter::add_bytes`] and
+/// [`BytesCounter::sub_bytes`]. The most bytes ever attributed to the type
+/// is kept as a high-water mark, retrievable with
+/// [`BytesCounter::peak_bytes`]; like [`PeakCounter`], raising it costs an
+/// extra atomic operation only when a new peak is set. The totals live in
+/// one padded struct, and are updated with [`Ordering::Relaxed`].
+///
+/// ```
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::BytesCounter<{ std::mem::size_of::<Buffer>() }>")]
+/// pub struct Buffer {
+///     data: Vec<u8>,
+///     _instance: Instance<Self>,
+/// }
+///
+/// impl Buffer {
+///     fn new(len: usize) -> Self {
+///         Buffer::counter().add_bytes(len);
+///         Buffer { data: vec![0; len], _instance: Instance::new() }
+///     }
+/// }
+///
+/// impl Drop for Buffer {
+///     fn drop(&mut self) {
+///         Buffer::counter().sub_bytes(self.data.len());
+///     }
+/// }
+///
+/// let _buffers = [Buffer::new(1024), Buffer::new(1024)];
+/// assert_eq!(Buffer::instances(), 2);
+/// assert_eq!(
+///     Buffer::counter().bytes(),
+///     2 * (1024 + std::mem::size_of::<Buffer>())
+/// );
+///
+/// drop(_buffers);
+/// assert_eq!(Buffer::counter().bytes(), 0);
+/// assert_eq!(
+///     Buffer::peak_bytes(),
+///     Some(2 * (1024 + std::mem::size_of::<Buffer>()))
+/// );
+/// ```
+pub struct BytesCounter<const SIZE: usize = 0> {
+    counter: CachePadded<Bytes>,
+}
+
+struct Bytes {
+    live: AtomicIsize,
+    bytes: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl<const SIZE: usize> BytesCounter<SIZE> {
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            counter: CachePadded::new(Bytes {
+                live: AtomicIsize::new(0),
+                bytes: AtomicUsize::new(0),
+                peak: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Adds `bytes` to the byte total of this counter.
+    #[inline(always)]
+    pub fn add_bytes(&self, bytes: usize) {
+        let total = self
+            .counter
+            .bytes
+            .fetch_add(bytes, Ordering::Relaxed)
+            .wrapping_add(bytes);
+        if total > self.counter.peak.load(Ordering::Relaxed) {
+            let _ = self.counter.peak.fetch_max(total, Ordering::Relaxed);
+        }
+    }
+
+    /// Subtracts `bytes` from the byte total of this counter.
+    #[inline(always)]
+    pub fn sub_bytes(&self, bytes: usize) {
+        let _ = self.counter.bytes.fetch_sub(bytes, Ordering::Relaxed);
+    }
+
+    /// Eventually retrieve the byte total of this counter.
+    #[inline(always)]
+    pub fn bytes(&self) -> usize {
+        self.counter.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Eventually retrieve the highest byte total this counter has held.
+    #[inline(always)]
+    pub fn peak_bytes(&self) -> usize {
+        self.counter.peak.load(Ordering::Relaxed)
+    }
+
+    /// Resets the peak byte total of this counter to its current byte
+    /// total, producing the previous peak.
+    ///
+    /// This lets the peak be reported per interval.
+    pub fn reset_peak_bytes(&self) -> usize {
+        self.counter.peak.swap(
+            self.counter.bytes.load(Ordering::Relaxed),
+            Ordering::Relaxed,
+        )
+    }
+}
+
+/// The values carried by a [`BytesCounter`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BytesStats {
+    /// The current value of the counter.
+    pub live: isize,
+    /// The byte total of the counter.
+    pub bytes: usize,
+    /// The highest byte total the counter has held.
+    pub peak_bytes: usize,
+}
+
+impl<const SIZE: usize> FetchAll for BytesCounter<SIZE> {
+    type Stats = BytesStats;
+
+    /// Retrieves the count, the byte total and the peak byte total of this
+    /// counter.
+    ///
+    /// The count and the byte total are independent totals, read one after
+    /// the other. The peak is read afterwards, and is at least the byte
+    /// total.
+    #[inline(always)]
+    fn fetch_all(&self) -> BytesStats {
+        let live = self.counter.live.load(Ordering::Relaxed);
+        let bytes = self.counter.bytes.load(Ordering::Relaxed);
+        let peak_bytes = self.counter.peak.load(Ordering::Relaxed).max(bytes);
+        BytesStats {
+            live,
+            bytes,
+            peak_bytes,
+        }
+    }
+}
+
+impl<const SIZE: usize> Default for BytesCounter<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const SIZE: usize> Counter for BytesCounter<SIZE> {
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: isize) {
+        let _ = self.counter.live.fetch_add(n, Ordering::Relaxed);
+        if SIZE != 0 {
+            self.add_bytes((n as usize).wrapping_mul(SIZE));
+        }
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: isize) {
+        let _ = self.counter.live.fetch_sub(n, Ordering::Relaxed);
+        if SIZE != 0 {
+            self.sub_bytes((n as usize).wrapping_mul(SIZE));
+        }
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> isize {
+        self.counter.live.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    fn fetch_bytes(&self) -> Option<usize> {
+        Some(self.bytes())
+    }
+
+    #[inline(always)]
+    fn fetch_peak_bytes(&self) -> Option<usize> {
+        Some(self.peak_bytes())
+    }
+}
+
+#[cfg(test)]
+mod bytes_counter {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = BytesCounter::<8>::ZERO;
+        assert_eq!(counter.fetch(), 0);
+        assert_eq!(counter.bytes(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = BytesCounter::<8>::ZERO;
+        counter.add_assign(2);
+        assert_eq!(counter.fetch(), 2);
+        assert_eq!(counter.bytes(), 16);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = BytesCounter::<8>::ZERO;
+        counter.add_assign(2);
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), 1);
+        assert_eq!(counter.bytes(), 8);
+    }
+
+    #[test]
+    fn explicit() {
+        let counter = BytesCounter::<0>::ZERO;
+        counter.add_assign(1);
+        counter.add_bytes(100);
+        assert_eq!(counter.bytes(), 100);
+        counter.sub_bytes(60);
+        assert_eq!(counter.bytes(), 40);
+    }
+
+    #[test]
+    fn fetch_all() {
+        let counter = BytesCounter::<8>::ZERO;
+        counter.add_assign(2);
+        counter.add_bytes(4);
+        counter.sub_bytes(6);
+        assert_eq!(
+            counter.fetch_all(),
+            BytesStats {
+                live: 2,
+                bytes: 14,
+                peak_bytes: 20
+            }
+        );
+    }
+
+    #[test]
+    fn peak() {
+        let counter = BytesCounter::<8>::ZERO;
+        counter.add_assign(3);
+        counter.sub_assign(2);
+        assert_eq!(counter.bytes(), 8);
+        assert_eq!(counter.peak_bytes(), 24);
+        assert_eq!(counter.fetch_peak_bytes(), Some(24));
+        assert_eq!(counter.reset_peak_bytes(), 24);
+        assert_eq!(counter.peak_bytes(), 8);
+        counter.add_bytes(1);
+        assert_eq!(counter.peak_bytes(), 9);
+    }
+}
+
+/// A [`Counter`] that delegates to `C`, printing every change to standard
+/// error.
+///
+/// This can be layered onto any counter via the derive's `Counter =`
+/// attribute, to trace the population of a type while debugging:
+///
+/// ```
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::Logging<type_census::counter::RelaxedCounter>")]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// // Prints `Logging<RelaxedCounter>@0x…: +1 → 1` or similar.
+/// let _foo = Foo { _instance: Instance::new() };
+/// assert_eq!(Foo::instances(), 1);
+/// ```
+pub struct Logging<C> {
+    counter: C,
+}
+
+impl<C> Logging<C>
+where
+    C: Counter,
+    C::Primitive: Copy + std::fmt::Display,
+{
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self { counter: C::ZERO }
+    }
+
+    /// Produces the counter this delegates to.
+    pub fn inner(&self) -> &C {
+        &self.counter
+    }
+
+    fn log(&self, sign: char, n: C::Primitive) {
+        eprintln!(
+            "{}@{:p}: {sign}{n} → {}",
+            std::any::type_name::<Self>(),
+            self,
+            self.counter.fetch()
+        );
+    }
+}
+
+impl<C> Default for Logging<C>
+where
+    C: Counter,
+    C::Primitive: Copy + std::fmt::Display,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> Counter for Logging<C>
+where
+    C: Counter,
+    C::Primitive: Copy + std::fmt::Display,
+{
+    type Primitive = C::Primitive;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: C::Primitive) -> Option<isize> {
+        C::to_isize(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: C::Primitive) {
+        self.counter.add_assign(n);
+        self.log('+', n);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: C::Primitive) {
+        self.counter.sub_assign(n);
+        self.log('-', n);
+    }
+
+    #[inline(always)]
+    fn fetch_add(&self, n: C::Primitive) -> Result<C::Primitive, Unsupported> {
+        let before = self.counter.fetch_add(n);
+        self.log('+', n);
+        before
+    }
+
+    #[inline(always)]
+    fn fetch_sub(&self, n: C::Primitive) -> Result<C::Primitive, Unsupported> {
+        let before = self.counter.fetch_sub(n);
+        self.log('-', n);
+        before
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> C::Primitive {
+        self.counter.fetch()
+    }
+
+    fn fetch_exact(&self) -> C::Primitive {
+        self.counter.fetch_exact()
+    }
+
+    fn fetch_and_reset(&self) -> Result<C::Primitive, Unsupported> {
+        self.counter.fetch_and_reset()
+    }
+
+    fn fetch_bytes(&self) -> Option<usize> {
+        self.counter.fetch_bytes()
+    }
+
+    fn fetch_peak_bytes(&self) -> Option<usize> {
+        self.counter.fetch_peak_bytes()
+    }
+
+    fn fetch_underflows(&self) -> Option<u64> {
+        self.counter.fetch_underflows()
+    }
+}
+
+#[cfg(test)]
+mod logging {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = Logging::<RelaxedCounter>::ZERO;
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = Logging::<RelaxedCounter>::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = Logging::<RelaxedCounter>::ZERO;
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), -1);
+    }
+}
+
+/// A [`Counter`] that mirrors every change into two counters, `A` and `B`.
+///
+/// Reads are served by `A`, except that [byte totals](Counter::fetch_bytes)
+/// are served by `B` if `A` doesn't track bytes; `B` is reachable with
+/// [`Tee::second`]. This can be layered onto any pair of counters via the
+/// derive's `Counter =` attribute, e.g. to keep a cheap counter for hot-path
+/// reads alongside a [`StatsCounter`] for reporting:
+///
+/// ```
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::Tee<
+///     type_census::counter::AtomicCounter<std::sync::atomic::AtomicIsize>,
+///     type_census::counter::StatsCounter,
+/// >")]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// let foo = Foo { _instance: Instance::new() };
+/// drop(foo);
+/// assert_eq!(Foo::instances(), 0);
+/// assert_eq!(Foo::counter().second().stats().peak, 1);
+/// ```
+pub struct Tee<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Tee<A, B> {
+    /// Produces the counter that serves reads.
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// Produces the mirrored counter.
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+}
+
+impl<A, B> Tee<A, B>
+where
+    A: Counter,
+    A::Primitive: Copy,
+    B: Counter<Primitive = A::Primitive>,
+{
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self {
+            first: A::ZERO,
+            second: B::ZERO,
+        }
+    }
+}
+
+impl<A, B> Default for Tee<A, B>
+where
+    A: Counter,
+    A::Primitive: Copy,
+    B: Counter<Primitive = A::Primitive>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A, B> Counter for Tee<A, B>
+where
+    A: Counter,
+    A::Primitive: Copy,
+    B: Counter<Primitive = A::Primitive>,
+{
+    type Primitive = A::Primitive;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: A::Primitive) -> Option<isize> {
+        A::to_isize(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, n: A::Primitive) {
+        self.first.add_assign(n);
+        self.second.add_assign(n);
+    }
+
+    #[inline(always)]
+    fn sub_assign(&self, n: A::Primitive) {
+        self.first.sub_assign(n);
+        self.second.sub_assign(n);
+    }
+
+    #[inline(always)]
+    fn fetch_add(&self, n: A::Primitive) -> Result<A::Primitive, Unsupported> {
+        let before = self.first.fetch_add(n);
+        self.second.add_assign(n);
+        before
+    }
+
+    #[inline(always)]
+    fn fetch_sub(&self, n: A::Primitive) -> Result<A::Primitive, Unsupported> {
+        let before = self.first.fetch_sub(n);
+        self.second.sub_assign(n);
+        before
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> A::Primitive {
+        self.first.fetch()
+    }
+
+    fn fetch_exact(&self) -> A::Primitive {
+        self.first.fetch_exact()
+    }
+
+    fn fetch_bytes(&self) -> Option<usize> {
+        self.first
+            .fetch_bytes()
+            .or_else(|| self.second.fetch_bytes())
+    }
+
+    fn fetch_peak_bytes(&self) -> Option<usize> {
+        self.first
+            .fetch_peak_bytes()
+            .or_else(|| self.second.fetch_peak_bytes())
+    }
+
+    fn fetch_underflows(&self) -> Option<u64> {
+        self.first
+            .fetch_underflows()
+            .or_else(|| self.second.fetch_underflows())
+    }
+}
+
+#[cfg(test)]
+mod tee {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let counter = Tee::<RelaxedCounter, PeakCounter>::ZERO;
+        assert_eq!(counter.fetch(), 0);
+        assert_eq!(counter.second().fetch(), 0);
+    }
+
+    #[test]
+    fn increment() {
+        let counter = Tee::<RelaxedCounter, PeakCounter>::ZERO;
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 1);
+        assert_eq!(counter.second().fetch(), 1);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = Tee::<RelaxedCounter, PeakCounter>::ZERO;
+        counter.add_assign(1);
+        counter.sub_assign(1);
+        assert_eq!(counter.first().fetch(), 0);
+        assert_eq!(counter.second().peak(), 1);
+    }
+}
+
+/// A [`Counter`] that counts nothing.
+///
+/// Its operations are empty, and [`Counter::fetch`] always produces `0`.
+/// When compiled with `--cfg type_census_disabled`, this is the
+/// [`DefaultCounter`], so every type deriving
+/// [`Tabulate`](crate::Tabulate) without naming a counter costs nothing to
+/// construct or drop:
+///
+/// ```
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(Counter = "type_census::counter::NoopCounter")]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// let _foo = Foo { _instance: Instance::new() };
+/// assert_eq!(Foo::instances(), 0);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopCounter;
+
+impl NoopCounter {
+    /// Constructs a new counter holding the value of `0`.
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Counter for NoopCounter {
+    type Primitive = isize;
+    const ZERO: Self = Self::new();
+
+    #[inline(always)]
+    fn to_isize(n: isize) -> Option<isize> {
+        Some(n)
+    }
+
+    #[inline(always)]
+    fn add_assign(&self, _: isize) {}
+
+    #[inline(always)]
+    fn sub_assign(&self, _: isize) {}
+
+    #[inline(always)]
+    fn fetch(&self) -> isize {
+        0
+    }
+
+    #[inline(always)]
+    fn fetch_and_reset(&self) -> Result<isize, Unsupported> {
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod noop_counter {
+    use super::*;
+
+    #[test]
+    fn increment() {
+        let counter = NoopCounter::new();
+        counter.add_assign(1);
+        assert_eq!(counter.fetch(), 0);
+    }
+
+    #[test]
+    fn decrement() {
+        let counter = NoopCounter::new();
+        counter.sub_assign(1);
+        assert_eq!(counter.fetch(), 0);
+        assert_eq!(counter.fetch_and_reset(), Ok(0));
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_model {
+    use super::*;
+    use loom::sync::atomic::AtomicBool;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    #[test]
+    fn distributed_counter() {
+        loom::model(|| {
+            let counter = Arc::new(DistributedCounter::<2>::new());
+            let added = Arc::new(AtomicBool::new(false));
+            let adder = thread::spawn({
+                let counter = counter.clone();
+                let added = added.clone();
+                move || {
+                    counter.add_assign(1);
+                    added.store(true, Ordering::Release);
+                }
+            });
+            let subtracter = thread::spawn({
+                let counter = counter.clone();
+                move || {
+                    while !added.load(Ordering::Acquire) {
+                        thread::yield_now();
+                    }
+                    counter.sub_assign(1);
+                }
+            });
+            assert!((-1..=1).contains(&counter.fetch_exact()));
+            adder.join().unwrap();
+            subtracter.join().unwrap();
+            assert_eq!(counter.fetch(), 0);
+            assert_eq!(counter.fetch_exact(), 0);
+        });
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    #[test]
+    fn packed_counter() {
+        loom::model(|| {
+            let counter = Arc::new(PackedCounter::new());
+            let threads: Vec<_> = (0..2)
+                .map(|_| {
+                    let counter = counter.clone();
+                    thread::spawn(move || {
+                        counter.add_assign(1);
+                        counter.sub_assign(1);
+                    })
+                })
+                .collect();
+            let PackedStats { live, created } = counter.fetch_all();
+            assert!((0..=2).contains(&live));
+            assert!(created >= live as u32);
+            for thread in threads {
+                thread.join().unwrap();
+            }
+            assert_eq!(
+                counter.fetch_all(),
+                PackedStats {
+                    live: 0,
+                    created: 2
+                }
+            );
+        });
+    }
+}
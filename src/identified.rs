@@ -0,0 +1,104 @@
+//! Per-instance unique ids.
+
+use crate::{Instance, Tabulate};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A guard that tracks the lifetime of an instance of `T`, like
+/// [`Instance<T>`], and carries a unique id.
+///
+/// Construct one with [`Instance::new_identified`]. Ids are drawn from a
+/// single process-wide sequence shared by every tabulated type, so they
+/// increase monotonically in order of construction and are never reused.
+/// Include them in log lines to correlate messages about a specific object
+/// with census data.
+///
+/// ```
+/// use type_census::{IdentifiedInstance, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// pub struct Connection {
+///     _instance: IdentifiedInstance<Self>,
+/// }
+///
+/// impl Connection {
+///     pub fn new() -> Self {
+///         Self { _instance: Instance::new_identified() }
+///     }
+///
+///     pub fn id(&self) -> u64 {
+///         self._instance.id()
+///     }
+/// }
+///
+/// let a = Connection::new();
+/// let b = Connection::new();
+/// assert!(a.id() < b.id());
+/// assert_eq!(Connection::instances(), 2);
+/// ```
+pub struct IdentifiedInstance<T>
+where
+    T: Tabulate,
+{
+    instance: Instance<T>,
+    id: u64,
+}
+
+/// Produces the next id in the process-wide sequence.
+#[inline(always)]
+fn next_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+impl<T> IdentifiedInstance<T>
+where
+    T: Tabulate,
+{
+    /// Produces the unique id of this instance.
+    #[inline(always)]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<T> Instance<T>
+where
+    T: Tabulate,
+{
+    /// Constructs a new [`IdentifiedInstance<T>`], carrying a fresh unique
+    /// id.
+    #[inline(always)]
+    #[track_caller]
+    pub fn new_identified() -> IdentifiedInstance<T> {
+        IdentifiedInstance {
+            instance: Instance::new(),
+            id: next_id(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for IdentifiedInstance<T>
+where
+    T: Tabulate,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl<T> Clone for IdentifiedInstance<T>
+where
+    T: Tabulate,
+{
+    /// Clones this guard, counting a new instance of `T` with a fresh id.
+    #[inline(always)]
+    #[track_caller]
+    fn clone(&self) -> Self {
+        Self {
+            instance: self.instance.clone(),
+            id: next_id(),
+        }
+    }
+}
@@ -53,23 +53,65 @@
 //!     assert_eq!(Foo::<u8>::instances(), 10);
 //! }
 //! ```
+//!
+//! ## Disabling
+//! Counting can be compiled out of a build, without touching call sites, by
+//! passing `--cfg type_census_disabled` to the compiler:
+//! ```text
+//! RUSTFLAGS="--cfg type_census_disabled" cargo build --release
+//! ```
+//! Types deriving [`Tabulate`] without naming a counter then count with a
+//! [`counter::NoopCounter`], and subscriber notifications, limit waiter
+//! wakeups, backtrace capture and creation site tracking are compiled out
+//! for every type. Unlike a crate feature, this is chosen by whoever builds
+//! the final binary, so it can't be switched on by a dependency.
 #![deny(missing_docs)]
 
 use num_traits::identities::one;
 use std::marker::PhantomData;
 
+#[cfg(feature = "capture-backtrace")]
+pub mod backtrace;
+mod batch;
+mod cell;
+pub mod census;
+pub mod clock;
 pub mod counter;
+mod identified;
+pub mod lifetime;
+mod limit;
+pub mod pair;
+pub mod registry;
+#[cfg(feature = "creation-sites")]
+pub mod sites;
+pub mod snapshot;
+mod sync;
+#[cfg(feature = "tracing")]
+mod traced;
+mod weighted;
+
+pub use batch::Instances;
+pub use cell::InstanceCell;
+pub use identified::IdentifiedInstance;
+pub use lifetime::TimedInstance;
+pub use limit::LimitExceeded;
+pub use registry::registry;
+#[cfg(feature = "tracing")]
+pub use traced::TracedInstance;
+pub use weighted::WeightedInstance;
 
 use counter::Counter;
 
 /// Automatically derive the implementation of [`Tabulate`].
 ///
-/// By default, this uses [`counter::RelaxedCounter`] to count the instances.
+/// By default, this uses [`counter::DefaultCounter`] to count the instances:
+/// a [`counter::RelaxedCounter`] or, when [disabled](crate#disabling), a
+/// [`counter::NoopCounter`] that compiles counting out entirely.
 /// You can use a different counter type like so:
 /// ```
 /// // 1. import these two items:
 /// use type_census::{Instance, Tabulate};
-/// 
+///
 /// // 2. Derive `Tabulate`
 /// // This will count instances with a `DistributedCounter` with 32 buckets.
 /// #[derive(Clone, Tabulate)]
@@ -80,8 +122,112 @@ use counter::Counter;
 ///     _instance: Instance<Self>,
 /// }
 /// ```
+///
+/// To run a function the first time the counter of a type is touched (e.g.,
+/// to register the type with an external system), name it with
+/// `on_first_touch`. The function is passed the [`Metadata`] of the type,
+/// and is called at most once, even for generic types:
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use type_census::{Instance, Metadata, Tabulate};
+///
+/// static TOUCHED: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn touched(metadata: Metadata) {
+///     assert_eq!(metadata.name, "Foo");
+///     TOUCHED.fetch_add(1, Ordering::SeqCst);
+/// }
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(on_first_touch = "touched")]
+/// pub struct Foo<T> {
+///     v: T,
+///     _instance: Instance<Self>,
+/// }
+///
+/// assert_eq!(TOUCHED.load(Ordering::SeqCst), 0);
+/// let _a = Foo { v: 0u8, _instance: Instance::new() };
+/// let _b = Foo { v: 0i8, _instance: Instance::new() };
+/// assert_eq!(TOUCHED.load(Ordering::SeqCst), 1);
+/// ```
+///
+/// The function must not itself touch the counter of the type.
+///
+/// By default, all instantiations of a generic type share one counter. To
+/// count instances separately for each instantiation of one generic
+/// parameter, name it with `per_param`. The parameter must be bounded by
+/// `'static`:
+/// ```
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(per_param(C))]
+/// pub struct Message<C: 'static, P> {
+///     codec: C,
+///     payload: P,
+///     _instance: Instance<Self>,
+/// }
+///
+/// let _a = Message { codec: 0u8, payload: (), _instance: Instance::new() };
+/// let _b = Message { codec: 0u8, payload: 0, _instance: Instance::new() };
+/// let _c = Message { codec: 0i8, payload: (), _instance: Instance::new() };
+///
+/// assert_eq!(Message::<u8, String>::instances(), 2);
+/// assert_eq!(Message::<i8, String>::instances(), 1);
+/// ```
+///
+/// Each instantiation is registered under its own name in the global
+/// [registry](crate::registry), e.g. `my_app::Message<u8>`; see
+/// [`Metadata::path`].
+///
+/// If a type has no room for an [`Instance<Self>`] field, the derive can
+/// instead generate a `tabulated` constructor helper that counts the value,
+/// and a `Drop` impl that uncounts it. The type must not otherwise
+/// implement `Drop`, and every value must be passed through `tabulated`
+/// exactly once:
+/// ```
+/// use type_census::Tabulate;
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(hook = "drop")]
+/// #[repr(C)]
+/// pub struct Header {
+///     magic: u32,
+///     len: u32,
+/// }
+///
+/// let header = Header { magic: 0xCAFE, len: 0 }.tabulated();
+/// assert_eq!(Header::instances(), 1);
+/// drop(header);
+/// assert_eq!(Header::instances(), 0);
+/// ```
 pub use type_census_derive::Tabulate;
 
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+
+    #[cfg(feature = "auto-register")]
+    pub use inventory;
+
+    #[inline(always)]
+    pub fn increment<T: Tabulate>() {
+        #[cfg(feature = "lazy-register")]
+        T::ensure_registered();
+        census::add::<T>(one(), 1);
+    }
+
+    /// Registers `T`, which may not be `'static`, without its type id.
+    pub fn register<T: Tabulate>() {
+        registry().insert::<T>(None);
+    }
+
+    #[inline(always)]
+    pub fn decrement<T: Tabulate>() {
+        census::sub::<T>(one(), 1);
+    }
+}
+
 /// A zero-sized guard that tracks the lifetime of an instance of `T`.
 ///
 /// Constructing an `Instance<T>` increments the population count of `T`.
@@ -100,13 +246,87 @@ where
 {
     /// Constructs a new `Instance<T>`, representing the extant lifetime of
     /// an instance of `T`.
+    ///
+    /// With the `creation-sites` feature, the instance is attributed to the
+    /// caller's source location.
     #[inline(always)]
+    #[track_caller]
     pub fn new() -> Self {
-        T::counter().add_assign(one());
+        #[cfg(feature = "lazy-register")]
+        T::ensure_registered();
+        census::add::<T>(one(), 1);
+        Self::counted()
+    }
+
+    /// Constructs an `Instance<T>` for an instance of `T` that has already
+    /// been counted.
+    #[inline(always)]
+    #[track_caller]
+    fn counted() -> Self {
+        #[cfg(feature = "capture-backtrace")]
+        backtrace::capture::<T>();
+        #[cfg(feature = "creation-sites")]
+        sites::created::<T>(std::panic::Location::caller());
         Instance {
             _tabulated: PhantomData,
         }
     }
+
+    /// Transfers this instance from the population of `T` to the population
+    /// of `U`, another state of the same [state machine](State).
+    ///
+    /// The instance is counted as a `U` before it is uncounted as a `T`, so
+    /// the population of the machine as a whole never dips.
+    ///
+    /// ```
+    /// use type_census::{Instance, Tabulate};
+    ///
+    /// pub struct Conn;
+    ///
+    /// #[derive(Tabulate)]
+    /// #[Tabulate(state_of = "Conn")]
+    /// pub struct Idle {
+    ///     _instance: Instance<Self>,
+    /// }
+    ///
+    /// #[derive(Tabulate)]
+    /// #[Tabulate(state_of = "Conn")]
+    /// pub struct Active {
+    ///     _instance: Instance<Self>,
+    /// }
+    ///
+    /// impl Idle {
+    ///     fn activate(self) -> Active {
+    ///         Active { _instance: self._instance.transfer() }
+    ///     }
+    /// }
+    ///
+    /// let conn = Idle { _instance: Instance::new() };
+    /// let _conn = conn.activate();
+    /// assert_eq!(Idle::instances(), 0);
+    /// assert_eq!(Active::instances(), 1);
+    /// ```
+    #[inline(always)]
+    #[track_caller]
+    pub fn transfer<U>(self) -> Instance<U>
+    where
+        T: State,
+        U: State<Machine = T::Machine>,
+    {
+        let next = Instance::new();
+        drop(self);
+        next
+    }
+
+    /// Constructs a new [`WeakInstance<T>`], representing a non-owning
+    /// handle to this instance of `T`.
+    #[inline(always)]
+    pub fn downgrade(&self) -> WeakInstance<T>
+    where
+        T: TabulateWeak,
+    {
+        WeakInstance::new()
+    }
 }
 
 impl<T> std::fmt::Debug for Instance<T>
@@ -123,6 +343,7 @@ where
     T: Tabulate,
 {
     #[inline(always)]
+    #[track_caller]
     fn default() -> Self {
         Self::new()
     }
@@ -133,6 +354,7 @@ where
     T: Tabulate,
 {
     #[inline(always)]
+    #[track_caller]
     fn clone(&self) -> Self {
         Self::new()
     }
@@ -144,7 +366,7 @@ where
 {
     #[inline(always)]
     fn drop(&mut self) {
-        T::counter().sub_assign(one());
+        census::sub::<T>(one(), 1);
     }
 }
 
@@ -171,8 +393,8 @@ where
     T: Tabulate,
 {
     #[inline(always)]
-    fn partial_cmp(&self, _: &Self) -> Option<std::cmp::Ordering> {
-        Some(std::cmp::Ordering::Equal)
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
     }
 }
 
@@ -188,6 +410,255 @@ where
     }
 }
 
+/// A zero-sized guard that tracks the lifetime of a weak handle to an
+/// instance of `T`.
+///
+/// Weak handles are counted separately from [`Instance`]s, in
+/// [`TabulateWeak::weak_counter`]. Constructing a `WeakInstance<T>`
+/// increments the weak population count of `T`. Dropping a `WeakInstance<T>`
+/// decrements the weak population count of `T`.
+///
+/// ```
+/// use type_census::{Instance, Tabulate, TabulateWeak, WeakInstance};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(weak)]
+/// pub struct Foo {
+///     _instance: Instance<Self>,
+/// }
+///
+/// pub struct WeakFoo {
+///     _instance: WeakInstance<Foo>,
+/// }
+///
+/// let foo = Foo { _instance: Instance::new() };
+/// let weak = WeakFoo { _instance: foo._instance.downgrade() };
+/// assert_eq!(Foo::instances(), 1);
+/// assert_eq!(Foo::weak_instances(), 1);
+///
+/// drop(foo);
+/// assert_eq!(Foo::instances(), 0);
+/// assert_eq!(Foo::weak_instances(), 1);
+///
+/// drop(weak);
+/// assert_eq!(Foo::weak_instances(), 0);
+/// ```
+#[repr(transparent)]
+pub struct WeakInstance<T>
+where
+    T: TabulateWeak,
+{
+    _tabulated: PhantomData<T>,
+}
+
+impl<T> WeakInstance<T>
+where
+    T: TabulateWeak,
+{
+    /// Constructs a new `WeakInstance<T>`, representing the extant lifetime
+    /// of a weak handle to an instance of `T`.
+    #[inline(always)]
+    pub fn new() -> Self {
+        T::weak_counter().add_assign(one());
+        WeakInstance {
+            _tabulated: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for WeakInstance<T>
+where
+    T: TabulateWeak,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>()).finish()
+    }
+}
+
+impl<T> Default for WeakInstance<T>
+where
+    T: TabulateWeak,
+{
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for WeakInstance<T>
+where
+    T: TabulateWeak,
+{
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for WeakInstance<T>
+where
+    T: TabulateWeak,
+{
+    #[inline(always)]
+    fn drop(&mut self) {
+        T::weak_counter().sub_assign(one());
+    }
+}
+
+impl<T> std::hash::Hash for WeakInstance<T>
+where
+    T: TabulateWeak,
+{
+    #[inline(always)]
+    fn hash<H: std::hash::Hasher>(&self, _: &mut H) {}
+}
+
+impl<T> Ord for WeakInstance<T>
+where
+    T: TabulateWeak,
+{
+    #[inline(always)]
+    fn cmp(&self, _: &Self) -> std::cmp::Ordering {
+        std::cmp::Ordering::Equal
+    }
+}
+
+impl<T> PartialOrd for WeakInstance<T>
+where
+    T: TabulateWeak,
+{
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Eq for WeakInstance<T> where T: TabulateWeak {}
+
+impl<T> PartialEq for WeakInstance<T>
+where
+    T: TabulateWeak,
+{
+    #[inline(always)]
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+/// Static information describing a tabulated type.
+///
+/// Obtain the metadata of a type with [`Tabulate::metadata`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Metadata {
+    /// The name of the type, without its module path or generic parameters.
+    pub name: &'static str,
+    /// The path of the module in which the type is defined.
+    pub module_path: &'static str,
+    /// The size of the type, in bytes.
+    pub size: usize,
+    /// The alignment of the type, in bytes.
+    pub align: usize,
+    /// The name of the [`Counter`] type tracking instances of the type.
+    pub counter: &'static str,
+    /// Tags attached to the type with `#[Tabulate(tags(...))]`.
+    pub tags: &'static [&'static str],
+    /// Key-value pairs attached to the type with `#[Tabulate(meta(...))]`.
+    pub meta: &'static [(&'static str, &'static str)],
+    /// The instantiation of the generic parameter counted separately with
+    /// `#[Tabulate(per_param(...))]`, as named by [`std::any::type_name`],
+    /// if any.
+    pub param: Option<&'static str>,
+    /// The state machine of which the type is a state, as declared with
+    /// `#[Tabulate(state_of = "...")]` and named by
+    /// [`std::any::type_name`], if any.
+    pub state_of: Option<&'static str>,
+}
+
+impl Metadata {
+    /// Produces metadata for `T`, derived from [`std::any::type_name`].
+    pub fn of<T>() -> Self
+    where
+        T: Tabulate,
+    {
+        let path = std::any::type_name::<T>();
+        let path = path.split('<').next().unwrap_or(path);
+        let (module_path, name) = path.rsplit_once("::").unwrap_or(("", path));
+        Self {
+            name,
+            module_path,
+            size: std::mem::size_of::<T>(),
+            align: std::mem::align_of::<T>(),
+            counter: std::any::type_name::<T::Counter>(),
+            tags: &[],
+            meta: &[],
+            param: None,
+            state_of: None,
+        }
+    }
+
+    /// Replaces the name and module path of this metadata.
+    pub fn named(self, name: &'static str, module_path: &'static str) -> Self {
+        Self {
+            name,
+            module_path,
+            ..self
+        }
+    }
+
+    /// Replaces the tags of this metadata.
+    pub fn tagged(self, tags: &'static [&'static str]) -> Self {
+        Self { tags, ..self }
+    }
+
+    /// Replaces the key-value pairs of this metadata.
+    pub fn annotated(self, meta: &'static [(&'static str, &'static str)]) -> Self {
+        Self { meta, ..self }
+    }
+
+    /// Replaces the instantiation of the generic parameter counted
+    /// separately, by [`std::any::type_name`].
+    pub fn per_param(self, param: &'static str) -> Self {
+        Self {
+            param: Some(param),
+            ..self
+        }
+    }
+
+    /// Replaces the state machine of which the type is a state, by
+    /// [`std::any::type_name`].
+    pub fn state_of(self, machine: &'static str) -> Self {
+        Self {
+            state_of: Some(machine),
+            ..self
+        }
+    }
+
+    /// Produces the path of the type, as it is known to the
+    /// [registry](crate::registry): its module path and name, followed by
+    /// its [`param`](Metadata::param) in angle brackets, if any; e.g.
+    /// `my_app::Message<u8>`.
+    pub fn path(&self) -> String {
+        let mut path = String::from(self.module_path);
+        if !path.is_empty() {
+            path.push_str("::");
+        }
+        path.push_str(self.name);
+        if let Some(param) = self.param {
+            path.push('<');
+            path.push_str(param);
+            path.push('>');
+        }
+        path
+    }
+
+    /// Produces the value associated with `key` in this metadata's key-value
+    /// pairs, if any.
+    pub fn get(&self, key: &str) -> Option<&'static str> {
+        self.meta.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    }
+}
+
 /// Track the population of `Self`.
 pub trait Tabulate: Sized {
     /// The type of the counter used to track instances of `Self`.
@@ -196,8 +667,238 @@ pub trait Tabulate: Sized {
     /// Produces a reference to the counter tracking instances of `Self`.
     fn counter() -> &'static Self::Counter;
 
+    /// Produces static information describing `Self`.
+    ///
+    /// ```
+    /// use type_census::{Instance, Tabulate};
+    ///
+    /// #[derive(Tabulate)]
+    /// #[Tabulate(tags("net", "io"), meta(owner = "team-net", ticket = "CAP-123"))]
+    /// pub struct Foo<T> {
+    ///     v: T,
+    ///     _instance: Instance<Self>,
+    /// }
+    ///
+    /// let metadata = Foo::<u64>::metadata();
+    /// assert_eq!(metadata.name, "Foo");
+    /// assert_eq!(metadata.module_path, module_path!());
+    /// assert_eq!(metadata.size, 8);
+    /// assert_eq!(metadata.align, 8);
+    /// assert_eq!(metadata.counter, std::any::type_name::<type_census::counter::RelaxedCounter>());
+    /// assert_eq!(metadata.tags, ["net", "io"]);
+    /// assert_eq!(metadata.get("owner"), Some("team-net"));
+    /// assert_eq!(metadata.get("ticket"), Some("CAP-123"));
+    /// ```
+    fn metadata() -> Metadata {
+        Metadata::of::<Self>()
+    }
+
+    /// Registers `Self` in the global [registry](crate::registry), if it
+    /// isn't already.
+    ///
+    /// With the `lazy-register` feature, [`Instance::new`] calls this, so
+    /// that every type is registered by the time its first instance exists.
+    /// The derived implementation is cheap after the first call.
+    fn ensure_registered() {
+        registry().insert::<Self>(None);
+    }
+
     /// Produces the number of extant instances of `T`.
     fn instances() -> <Self::Counter as Counter>::Primitive {
         Self::counter().fetch()
     }
+
+    /// Limits the population of `Self` to `limit` instances, as enforced by
+    /// [`Instance::try_new`].
+    ///
+    /// Types sharing a counter (such as the instantiations of a generic type)
+    /// share a limit. Lowering the limit below the current population does
+    /// not affect extant instances.
+    fn set_limit(limit: usize) {
+        limit::set::<Self>(Some(limit));
+    }
+
+    /// Removes the population limit of `Self`, if any.
+    fn remove_limit() {
+        limit::set::<Self>(None);
+    }
+
+    /// Produces the population limit of `Self`, if any.
+    fn limit() -> Option<usize> {
+        limit::get::<Self>()
+    }
+
+    /// Produces the lifetimes recorded for the [timed](TimedInstance)
+    /// instances of `Self`, including a histogram of their distribution.
+    ///
+    /// See [`lifetime::of`].
+    fn lifetimes() -> lifetime::Lifetimes {
+        lifetime::of::<Self>()
+    }
+
+    /// Produces the mean lifetime of the [timed](TimedInstance) instances of
+    /// `Self` dropped so far, or `None` if none have been dropped.
+    ///
+    /// See [`Lifetimes::mean`](lifetime::Lifetimes::mean).
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use type_census::clock::MockClock;
+    /// use type_census::{Tabulate, TimedInstance};
+    ///
+    /// static CLOCK: MockClock = MockClock::new();
+    ///
+    /// #[derive(Tabulate)]
+    /// pub struct Request {
+    ///     _instance: TimedInstance<Self, &'static MockClock>,
+    /// }
+    ///
+    /// assert_eq!(Request::mean_lifetime(), None);
+    ///
+    /// for millis in [10, 30] {
+    ///     let request = Request { _instance: TimedInstance::with_clock(&CLOCK) };
+    ///     CLOCK.advance(Duration::from_millis(millis));
+    ///     drop(request);
+    /// }
+    ///
+    /// assert_eq!(Request::mean_lifetime(), Some(Duration::from_millis(20)));
+    /// assert!(Request::lifetime_percentile(50.0).unwrap() >= Duration::from_millis(10));
+    /// ```
+    fn mean_lifetime() -> Option<std::time::Duration> {
+        lifetime::of::<Self>().mean()
+    }
+
+    /// Produces the `p`th percentile of the lifetimes of the
+    /// [timed](TimedInstance) instances of `Self` dropped so far, or `None`
+    /// if none have been dropped.
+    ///
+    /// `p` is clamped to `0.0..=100.0`. Lifetimes are recorded in a
+    /// [histogram](lifetime::Histogram), so this is the upper bound of the
+    /// bucket holding the percentile; see
+    /// [`Histogram::quantile`](lifetime::Histogram::quantile).
+    fn lifetime_percentile(p: f64) -> Option<std::time::Duration> {
+        lifetime::of::<Self>().histogram.quantile(p / 100.0)
+    }
+
+    /// Produces the most bytes ever attributed to the instances of `Self`,
+    /// if its counter tracks bytes, such as a [`counter::BytesCounter`].
+    ///
+    /// See [`Counter::fetch_peak_bytes`].
+    fn peak_bytes() -> Option<usize> {
+        Self::counter().fetch_peak_bytes()
+    }
+}
+
+/// Track the population of weak handles to `Self`, separately from the
+/// population of `Self`.
+///
+/// Derive this trait alongside [`Tabulate`] with `#[Tabulate(weak)]`.
+pub trait TabulateWeak: Tabulate {
+    /// Produces a reference to the counter tracking weak handles to `Self`.
+    fn weak_counter() -> &'static Self::Counter;
+
+    /// Produces the number of extant weak handles to `Self`.
+    fn weak_instances() -> <Self::Counter as Counter>::Primitive {
+        Self::weak_counter().fetch()
+    }
+}
+
+/// A state of a state machine, such as one of a family of typestate structs.
+///
+/// Derive this trait alongside [`Tabulate`] with
+/// `#[Tabulate(state_of = "Machine")]`, where `Machine` names any `'static`
+/// type standing for the machine as a whole. The states of a machine may be
+/// separate types, or the instantiations of a generic type counted with
+/// `per_param`:
+/// ```
+/// use type_census::{census, registry, Instance, Tabulate};
+///
+/// pub struct Idle;
+/// pub struct Active;
+/// pub struct ConnMachine;
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(per_param(S), state_of = "ConnMachine")]
+/// pub struct Conn<S: 'static> {
+///     state: S,
+///     _instance: Instance<Self>,
+/// }
+///
+/// impl Conn<Idle> {
+///     fn activate(self) -> Conn<Active> {
+///         Conn { state: Active, _instance: self._instance.transfer() }
+///     }
+/// }
+///
+/// registry().register::<Conn<Idle>>();
+/// registry().register::<Conn<Active>>();
+///
+/// let _idle = Conn { state: Idle, _instance: Instance::new() };
+/// let _active = Conn { state: Idle, _instance: Instance::new() }.activate();
+///
+/// let states = census::states::<ConnMachine>();
+/// assert_eq!(states.len(), 2);
+/// assert!(states.iter().all(|(_, count)| count == 1));
+/// ```
+///
+/// Instances move between states with [`Instance::transfer`], and the
+/// per-state breakdown of a machine is reported by [`census::states`].
+pub trait State: Tabulate {
+    /// The type standing for the state machine of which `Self` is a state.
+    type Machine: 'static;
+}
+
+/// A zero-sized façade for querying the population of a tabulated type.
+///
+/// Derive a façade named after the tabulated type with
+/// `#[Tabulate(census)]`. The façade provides the methods of this trait as
+/// inherent methods, so they can be called without importing anything, and
+/// can be passed by value to generic reporting code. It also provides a
+/// `watch` method, which [subscribes](census::subscribe) to changes in the
+/// population of only its type:
+/// ```
+/// use type_census::{Census, Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(census)]
+/// pub struct Foo<T> {
+///     v: T,
+///     _instance: Instance<Self>,
+/// }
+///
+/// fn report(census: impl Census<Counter = type_census::counter::RelaxedCounter>) -> isize {
+///     census.instances()
+/// }
+///
+/// let _foo = Foo { v: 0u8, _instance: Instance::new() };
+/// assert_eq!(FooCensus.instances(), 1);
+/// assert_eq!(report(FooCensus), 1);
+/// ```
+pub trait Census {
+    /// The type of the counter used to track instances.
+    type Counter: Counter;
+
+    /// Produces a reference to the counter tracking instances.
+    fn counter(&self) -> &'static Self::Counter;
+
+    /// Produces the number of extant instances.
+    fn instances(&self) -> <Self::Counter as Counter>::Primitive {
+        self.counter().fetch()
+    }
+
+    /// Produces the most bytes ever attributed to the instances, if the
+    /// counter tracks bytes.
+    ///
+    /// See [`Counter::fetch_peak_bytes`].
+    fn peak(&self) -> Option<usize> {
+        self.counter().fetch_peak_bytes()
+    }
+
+    /// Produces the number of bytes attributed to the extant instances, if
+    /// the counter tracks bytes.
+    ///
+    /// See [`Counter::fetch_bytes`].
+    fn bytes(&self) -> Option<usize> {
+        self.counter().fetch_bytes()
+    }
 }
@@ -0,0 +1,483 @@
+//! Measuring how long instances live.
+//!
+//! An [`Instance`] only counts the extant instances of a type. To also
+//! measure how long they live, use a [`TimedInstance`] instead: it records
+//! when it was constructed, and reports its lifetime to a per-type recorder
+//! when it is dropped. Read the recorded lifetimes of a type with [`of`], or
+//! with [`Tabulate::lifetimes`], and export those of every registered type
+//! as Prometheus summaries with [`to_prometheus`].
+//!
+//! ## Example
+//! ```
+//! use std::time::Duration;
+//! use type_census::clock::MockClock;
+//! use type_census::lifetime::{self, TimedInstance};
+//! use type_census::Tabulate;
+//!
+//! static CLOCK: MockClock = MockClock::new();
+//!
+//! #[derive(Tabulate)]
+//! pub struct Request {
+//!     _instance: TimedInstance<Self, &'static MockClock>,
+//! }
+//!
+//! let a = Request { _instance: TimedInstance::with_clock(&CLOCK) };
+//! let b = Request { _instance: TimedInstance::with_clock(&CLOCK) };
+//! CLOCK.advance(Duration::from_millis(10));
+//! drop(a);
+//! CLOCK.advance(Duration::from_millis(20));
+//! drop(b);
+//!
+//! let lifetimes = lifetime::of::<Request>();
+//! assert_eq!(lifetimes.dropped, 2);
+//! assert_eq!(lifetimes.total, Duration::from_millis(40));
+//! assert_eq!(lifetimes.max, Duration::from_millis(30));
+//! assert_eq!(lifetimes.mean(), Some(Duration::from_millis(20)));
+//! assert_eq!(lifetimes.histogram.count(), 2);
+//! ```
+
+use crate::census::grafana::promql_string;
+use crate::clock::{Clock, SystemClock};
+use crate::registry::registry;
+use crate::{Instance, Tabulate};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+/// The lifetimes recorded for a tabulated type, as produced by [`of`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lifetimes {
+    /// The number of timed instances dropped.
+    pub dropped: u64,
+    /// The sum of the lifetimes of the timed instances dropped.
+    pub total: Duration,
+    /// The longest lifetime of a timed instance dropped.
+    pub max: Duration,
+    /// The distribution of the lifetimes of the timed instances dropped.
+    pub histogram: Histogram,
+}
+
+impl Lifetimes {
+    /// Produces the mean lifetime of the timed instances dropped, or `None`
+    /// if none have been dropped.
+    pub fn mean(&self) -> Option<Duration> {
+        let mean = self.total.as_nanos().checked_div(self.dropped.into())?;
+        Some(Duration::from_nanos(mean as u64))
+    }
+}
+
+/// The number of buckets in a [`Histogram`].
+const BUCKETS: usize = u64::BITS as usize + 1;
+
+/// A histogram of lifetimes, in fixed log-scale buckets.
+///
+/// Bucket `0` holds lifetimes of `0ns`, and each bucket `k > 0` holds
+/// lifetimes of at least `2^(k-1)` and less than `2^k` nanoseconds, so the
+/// buckets double in width from `1ns` up to centuries.
+///
+/// ```
+/// use std::time::Duration;
+/// use type_census::clock::MockClock;
+/// use type_census::{Tabulate, TimedInstance};
+///
+/// static CLOCK: MockClock = MockClock::new();
+///
+/// #[derive(Tabulate)]
+/// pub struct Buffer {
+///     _instance: TimedInstance<Self, &'static MockClock>,
+/// }
+///
+/// let long_lived = Buffer { _instance: TimedInstance::with_clock(&CLOCK) };
+/// for _ in 0..99 {
+///     let short_lived = Buffer { _instance: TimedInstance::with_clock(&CLOCK) };
+///     CLOCK.advance(Duration::from_micros(1));
+///     drop(short_lived);
+/// }
+/// CLOCK.advance(Duration::from_secs(10));
+/// drop(long_lived);
+///
+/// let histogram = Buffer::lifetimes().histogram;
+/// assert_eq!(histogram.count(), 100);
+/// assert_eq!(histogram.quantile(0.5), Some(Duration::from_nanos(1024)));
+/// assert!(histogram.quantile(1.0).unwrap() > Duration::from_secs(10));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Histogram {
+    /// The number of lifetimes in each bucket, without trailing empty
+    /// buckets.
+    counts: Vec<u64>,
+}
+
+impl Histogram {
+    /// Produces the index of the bucket holding a lifetime of `nanos`.
+    fn bucket_of(nanos: u64) -> usize {
+        (u64::BITS - nanos.leading_zeros()) as usize
+    }
+
+    /// Produces the exclusive upper bound of the lifetimes in bucket `k`.
+    fn upper_bound(k: usize) -> Duration {
+        let nanos = 1u128 << k;
+        Duration::new(
+            (nanos / 1_000_000_000) as u64,
+            (nanos % 1_000_000_000) as u32,
+        )
+    }
+
+    /// Produces the exclusive upper bound and number of lifetimes of each
+    /// bucket, in ascending order, up to the last non-empty bucket.
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(k, &count)| (Self::upper_bound(k), count))
+    }
+
+    /// Produces the number of lifetimes in this histogram.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Produces the exclusive upper bound of the bucket holding the
+    /// `q`-quantile of the lifetimes in this histogram, or `None` if it is
+    /// empty.
+    ///
+    /// `q` is clamped to `0.0..=1.0`.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        self.buckets().find_map(|(upper_bound, n)| {
+            seen += n;
+            (seen >= rank).then_some(upper_bound)
+        })
+    }
+}
+
+/// Records the lifetimes of the timed instances of a type.
+struct Recorder {
+    dropped: AtomicU64,
+    /// The sum of the lifetimes, in nanoseconds.
+    total: AtomicU64,
+    /// The longest lifetime, in nanoseconds.
+    max: AtomicU64,
+    /// The number of lifetimes in each [`Histogram`] bucket.
+    buckets: [AtomicU64; BUCKETS],
+}
+
+impl Recorder {
+    const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const ZERO: AtomicU64 = AtomicU64::new(0);
+        Self {
+            dropped: ZERO,
+            total: ZERO,
+            max: ZERO,
+            buckets: [ZERO; BUCKETS],
+        }
+    }
+
+    fn record(&self, lifetime: Duration) {
+        let nanos = u64::try_from(lifetime.as_nanos()).unwrap_or(u64::MAX);
+        let _ = self.dropped.fetch_add(1, Ordering::Relaxed);
+        let _ = self.total.fetch_add(nanos, Ordering::Relaxed);
+        let _ = self.max.fetch_max(nanos, Ordering::Relaxed);
+        let _ = self.buckets[Histogram::bucket_of(nanos)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn lifetimes(&self) -> Lifetimes {
+        let mut counts: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect();
+        while counts.last() == Some(&0) {
+            counts.pop();
+        }
+        Lifetimes {
+            dropped: self.dropped.load(Ordering::Relaxed),
+            total: Duration::from_nanos(self.total.load(Ordering::Relaxed)),
+            max: Duration::from_nanos(self.max.load(Ordering::Relaxed)),
+            histogram: Histogram { counts },
+        }
+    }
+}
+
+/// The recorder of each type with timed instances, by the address of its
+/// counter.
+static RECORDERS: RwLock<BTreeMap<usize, &'static Recorder>> = RwLock::new(BTreeMap::new());
+
+/// Produces the recorder of `T`, if any timed instance of `T` has been
+/// dropped.
+fn get<T>() -> Option<&'static Recorder>
+where
+    T: Tabulate,
+{
+    let key = T::counter() as *const T::Counter as usize;
+    let recorders = RECORDERS.read().unwrap_or_else(PoisonError::into_inner);
+    recorders.get(&key).copied()
+}
+
+/// Produces the recorder of `T`, creating it if need be.
+fn get_or_insert<T>() -> &'static Recorder
+where
+    T: Tabulate,
+{
+    if let Some(recorder) = get::<T>() {
+        return recorder;
+    }
+    let key = T::counter() as *const T::Counter as usize;
+    let mut recorders = RECORDERS.write().unwrap_or_else(PoisonError::into_inner);
+    recorders
+        .entry(key)
+        .or_insert_with(|| Box::leak(Box::new(Recorder::new())))
+}
+
+/// Produces the lifetimes recorded for the timed instances of `T`.
+///
+/// Types sharing a counter (such as the instantiations of a generic type)
+/// share a recorder. Only instances that have been dropped are included.
+pub fn of<T>() -> Lifetimes
+where
+    T: Tabulate,
+{
+    get::<T>().map_or_else(Lifetimes::default, Recorder::lifetimes)
+}
+
+/// Produces the lifetimes recorded for every type in the global
+/// [registry](crate::registry) with timed instances, by path, in
+/// registration order.
+///
+/// Types whose timed instances have not yet been dropped are omitted.
+pub fn all() -> Vec<(&'static str, Lifetimes)> {
+    let entries: Vec<_> = registry().entries().collect();
+    let recorders = RECORDERS.read().unwrap_or_else(PoisonError::into_inner);
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let recorder = recorders.get(&entry.counter())?;
+            Some((entry.name(), recorder.lifetimes()))
+        })
+        .collect()
+}
+
+/// Renders the lifetimes of every registered type with timed instances as
+/// Prometheus summaries, in the text exposition format.
+///
+/// Each type is a series of the summary `metric`, labeled with
+/// `type="<path>"`: one sample per each of `quantiles`, in seconds, and the
+/// `_sum` and `_count` of the lifetimes. Scrape it to monitor lifetime
+/// objectives, such as sessions living less than 30s at the 99th
+/// percentile. Quantiles are read from the [`Histogram`] of each type, so
+/// each is the upper bound of the bucket holding it: at most twice the
+/// true quantile. Quantiles are clamped to `0.0..=1.0`.
+///
+/// ```
+/// use std::time::Duration;
+/// use type_census::clock::MockClock;
+/// use type_census::{lifetime, registry, Tabulate, TimedInstance};
+///
+/// static CLOCK: MockClock = MockClock::new();
+///
+/// #[derive(Tabulate)]
+/// pub struct Session {
+///     _instance: TimedInstance<Self, &'static MockClock>,
+/// }
+///
+/// registry().register::<Session>();
+///
+/// let session = Session { _instance: TimedInstance::with_clock(&CLOCK) };
+/// CLOCK.advance(Duration::from_secs(1));
+/// drop(session);
+///
+/// let summaries = lifetime::to_prometheus("session_lifetime_seconds", &[0.5, 0.99]);
+/// let session = concat!(module_path!(), "::Session");
+/// assert!(summaries.contains("# TYPE session_lifetime_seconds summary\n"));
+/// assert!(summaries.contains(&format!(
+///     "session_lifetime_seconds{{type=\"{session}\",quantile=\"0.99\"}} 1.073741824\n"
+/// )));
+/// assert!(summaries.contains(&format!("session_lifetime_seconds_sum{{type=\"{session}\"}} 1\n")));
+/// assert!(summaries.contains(&format!("session_lifetime_seconds_count{{type=\"{session}\"}} 1\n")));
+/// ```
+pub fn to_prometheus(metric: &str, quantiles: &[f64]) -> String {
+    let mut out = format!(
+        "# HELP {metric} The lifetimes of timed instances, in seconds.\n# TYPE {metric} summary\n"
+    );
+    for (name, lifetimes) in all() {
+        let name = promql_string(name);
+        for &q in quantiles {
+            let q = q.clamp(0.0, 1.0);
+            let value = lifetimes
+                .histogram
+                .quantile(q)
+                .unwrap_or_default()
+                .as_secs_f64();
+            out.push_str(&format!(
+                "{metric}{{type=\"{name}\",quantile=\"{q}\"}} {value}\n"
+            ));
+        }
+        out.push_str(&format!(
+            "{metric}_sum{{type=\"{name}\"}} {}\n",
+            lifetimes.total.as_secs_f64()
+        ));
+        out.push_str(&format!(
+            "{metric}_count{{type=\"{name}\"}} {}\n",
+            lifetimes.dropped
+        ));
+    }
+    out
+}
+
+/// A guard that tracks the lifetime of an instance of `T`, like
+/// [`Instance<T>`], and records how long it lived when it is dropped.
+///
+/// The time is read from `C`. See the [module documentation](self) for an
+/// example.
+pub struct TimedInstance<T, C = SystemClock>
+where
+    T: Tabulate,
+    C: Clock,
+{
+    instance: Instance<T>,
+    created: Instant,
+    clock: C,
+}
+
+impl<T> TimedInstance<T>
+where
+    T: Tabulate,
+{
+    /// Constructs a new `TimedInstance<T>`, timed by the system clock.
+    #[inline(always)]
+    #[track_caller]
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl<T> Default for TimedInstance<T>
+where
+    T: Tabulate,
+{
+    #[inline(always)]
+    #[track_caller]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C> TimedInstance<T, C>
+where
+    T: Tabulate,
+    C: Clock,
+{
+    /// Constructs a new `TimedInstance<T, C>`, timed by `clock`.
+    #[inline(always)]
+    #[track_caller]
+    pub fn with_clock(clock: C) -> Self {
+        Self {
+            instance: Instance::new(),
+            created: clock.now(),
+            clock,
+        }
+    }
+
+    /// Produces the instant at which this instance was constructed.
+    pub fn created(&self) -> Instant {
+        self.created
+    }
+
+    /// Produces how long this instance has lived so far.
+    pub fn age(&self) -> Duration {
+        self.clock.now().saturating_duration_since(self.created)
+    }
+}
+
+impl<T> Instance<T>
+where
+    T: Tabulate,
+{
+    /// Constructs a new [`TimedInstance<T>`], which records how long it
+    /// lived when it is dropped.
+    #[inline(always)]
+    #[track_caller]
+    pub fn new_timed() -> TimedInstance<T> {
+        TimedInstance::new()
+    }
+}
+
+impl<T, C> std::fmt::Debug for TimedInstance<T, C>
+where
+    T: Tabulate,
+    C: Clock,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("created", &self.created)
+            .finish()
+    }
+}
+
+impl<T, C> Clone for TimedInstance<T, C>
+where
+    T: Tabulate,
+    C: Clock + Clone,
+{
+    /// Clones this guard, counting a new instance of `T` constructed now.
+    #[inline(always)]
+    #[track_caller]
+    fn clone(&self) -> Self {
+        Self {
+            instance: self.instance.clone(),
+            created: self.clock.now(),
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+impl<T, C> Drop for TimedInstance<T, C>
+where
+    T: Tabulate,
+    C: Clock,
+{
+    fn drop(&mut self) {
+        get_or_insert::<T>().record(self.age());
+    }
+}
+
+#[cfg(test)]
+mod histogram {
+    use super::*;
+
+    #[test]
+    fn buckets() {
+        assert_eq!(Histogram::bucket_of(0), 0);
+        assert_eq!(Histogram::bucket_of(1), 1);
+        assert_eq!(Histogram::bucket_of(2), 2);
+        assert_eq!(Histogram::bucket_of(3), 2);
+        assert_eq!(Histogram::bucket_of(1024), 11);
+        assert_eq!(Histogram::bucket_of(u64::MAX), BUCKETS - 1);
+        assert_eq!(Histogram::upper_bound(0), Duration::from_nanos(1));
+        assert_eq!(Histogram::upper_bound(11), Duration::from_nanos(2048));
+        assert!(Histogram::upper_bound(BUCKETS - 1) > Duration::from_nanos(u64::MAX));
+    }
+
+    #[test]
+    fn quantile() {
+        let recorder = Recorder::new();
+        assert_eq!(recorder.lifetimes().histogram.quantile(0.5), None);
+        recorder.record(Duration::from_nanos(3));
+        recorder.record(Duration::from_nanos(3));
+        recorder.record(Duration::from_nanos(100));
+        let histogram = recorder.lifetimes().histogram;
+        assert_eq!(histogram.buckets().count(), 8);
+        assert_eq!(histogram.quantile(0.0), Some(Duration::from_nanos(4)));
+        assert_eq!(histogram.quantile(0.5), Some(Duration::from_nanos(4)));
+        assert_eq!(histogram.quantile(1.0), Some(Duration::from_nanos(128)));
+    }
+}
@@ -0,0 +1,438 @@
+//! Per-type population limits.
+
+use crate::counter::Counter;
+use crate::{census, registry, Instance, Tabulate};
+use num_traits::identities::one;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::task::{Wake, Waker};
+use std::thread::Thread;
+use std::time::Duration;
+
+/// The limit and waiters of a type.
+struct Gate {
+    /// The limit of the type, or [`NO_LIMIT`].
+    limit: AtomicUsize,
+    /// The wakers of the threads and tasks waiting for admission to the
+    /// type, by the id of their [`Waiter`].
+    waiters: Mutex<Vec<(u64, Waker)>>,
+}
+
+/// The limit of a type without one; no population can exceed it.
+const NO_LIMIT: usize = usize::MAX;
+
+/// The gate of each type, by the address of its counter.
+///
+/// Gates are created on first use and never freed, so each thread caches
+/// them in [`CACHE`].
+static GATES: RwLock<BTreeMap<usize, &'static Gate>> = RwLock::new(BTreeMap::new());
+
+thread_local! {
+    /// The gates this thread has used, by the address of their counters.
+    static CACHE: RefCell<BTreeMap<usize, &'static Gate>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// The number of threads and tasks waiting for admission to any type.
+static WAITING: AtomicUsize = AtomicUsize::new(0);
+
+/// The longest a blocked thread parks before checking for admission again.
+const MAX_PARK: Duration = Duration::from_millis(50);
+
+/// Produces the gate of `T`.
+///
+/// Types sharing a counter (such as the instantiations of a generic type)
+/// share a gate, and so a limit.
+fn gate<T>() -> &'static Gate
+where
+    T: Tabulate,
+{
+    let key = T::counter() as *const T::Counter as usize;
+    CACHE
+        .try_with(|cache| *cache.borrow_mut().entry(key).or_insert_with(|| shared(key)))
+        .unwrap_or_else(|_| shared(key))
+}
+
+/// Produces the gate at `key` in [`GATES`], creating it if needed.
+#[cold]
+fn shared(key: usize) -> &'static Gate {
+    if let Some(gate) = GATES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&key)
+    {
+        return gate;
+    }
+    let mut gates = GATES.write().unwrap_or_else(PoisonError::into_inner);
+    gates.entry(key).or_insert_with(|| {
+        Box::leak(Box::new(Gate {
+            limit: AtomicUsize::new(NO_LIMIT),
+            waiters: Mutex::new(Vec::new()),
+        }))
+    })
+}
+
+/// Sets the limit of `T` to `limit`, or removes it if `limit` is `None`.
+pub(crate) fn set<T>(limit: Option<usize>)
+where
+    T: Tabulate,
+{
+    let gate = gate::<T>();
+    gate.limit
+        .store(limit.unwrap_or(NO_LIMIT), Ordering::SeqCst);
+    gate.wake();
+}
+
+/// Produces the limit of `T`, if any.
+pub(crate) fn get<T>() -> Option<usize>
+where
+    T: Tabulate,
+{
+    let limit = gate::<T>().limit.load(Ordering::SeqCst);
+    (limit != NO_LIMIT).then_some(limit)
+}
+
+/// Wakes the threads and tasks waiting for admission to `T`, if any, after
+/// an instance of `T` is released.
+#[inline(always)]
+pub(crate) fn released<T>()
+where
+    T: Tabulate,
+{
+    if !cfg!(type_census_disabled) && WAITING.load(Ordering::SeqCst) != 0 {
+        gate::<T>().wake();
+    }
+}
+
+impl Gate {
+    /// Wakes every waiter.
+    #[cold]
+    fn wake(&self) {
+        let wakers: Vec<Waker> = self
+            .waiters
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(_, waker)| waker.clone())
+            .collect();
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// A thread or task waiting for admission to a type.
+///
+/// It is registered with the type's gate at most once, and unregistered
+/// when dropped.
+struct Waiter {
+    gate: &'static Gate,
+    id: u64,
+}
+
+impl Waiter {
+    /// Constructs a waiter for admission to `T`, which is not yet
+    /// registered.
+    fn new<T>() -> Self
+    where
+        T: Tabulate,
+    {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self {
+            gate: gate::<T>(),
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Registers `waker` to be woken when an instance is released, replacing
+    /// the previously registered waker unless it would wake the same task.
+    fn register(&self, waker: &Waker) {
+        let mut waiters = self
+            .gate
+            .waiters
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match waiters.iter_mut().find(|(id, _)| *id == self.id) {
+            Some((_, registered)) => {
+                if !registered.will_wake(waker) {
+                    registered.clone_from(waker);
+                }
+            }
+            None => {
+                waiters.push((self.id, waker.clone()));
+                WAITING.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        let mut waiters = self
+            .gate
+            .waiters
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(index) = waiters.iter().position(|(id, _)| *id == self.id) {
+            waiters.swap_remove(index);
+            WAITING.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Wakes a thread blocked in [`Instance::new_blocking`].
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// The error produced by [`Instance::try_new`] when constructing an
+/// instance would exceed the [limit](Tabulate::set_limit) of its type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimitExceeded {
+    limit: usize,
+}
+
+impl LimitExceeded {
+    /// Produces the limit that would have been exceeded.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl std::fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "population limit of {} exceeded", self.limit)
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+impl<T> Instance<T>
+where
+    T: Tabulate,
+{
+    /// Constructs a new `Instance<T>`, unless doing so would exceed the
+    /// [limit](Tabulate::set_limit) of `T`.
+    ///
+    /// The instance is counted before the limit is checked, and uncounted
+    /// if the check fails, so the population of `T` never exceeds its limit
+    /// by way of this constructor; concurrent calls near the limit may both
+    /// fail. [`Instance::new`] does not check the limit. Subscribers are
+    /// only notified of instances that are kept.
+    ///
+    /// ```
+    /// use type_census::{Instance, Tabulate};
+    ///
+    /// #[derive(Debug, Tabulate)]
+    /// pub struct Connection {
+    ///     _instance: Instance<Self>,
+    /// }
+    ///
+    /// impl Connection {
+    ///     fn open() -> Result<Self, type_census::LimitExceeded> {
+    ///         Ok(Self { _instance: Instance::try_new()? })
+    ///     }
+    /// }
+    ///
+    /// Connection::set_limit(2);
+    /// let a = Connection::open().unwrap();
+    /// let _b = Connection::open().unwrap();
+    /// assert_eq!(Connection::open().unwrap_err().limit(), 2);
+    /// assert_eq!(Connection::instances(), 2);
+    ///
+    /// drop(a);
+    /// assert!(Connection::open().is_ok());
+    /// ```
+    #[track_caller]
+    pub fn try_new() -> Result<Self, LimitExceeded> {
+        #[cfg(feature = "lazy-register")]
+        T::ensure_registered();
+        let counter = T::counter();
+        let before = counter.fetch_add(one()).ok().and_then(T::Counter::to_isize);
+        if let Some(limit) = get::<T>() {
+            let count = before.map_or_else(registry::count::<T>, |before| before.wrapping_add(1));
+            if usize::try_from(count).is_ok_and(|count| count > limit) {
+                counter.sub_assign(one());
+                return Err(LimitExceeded { limit });
+            }
+        }
+        census::added::<T>(before, 1);
+        Ok(Instance::counted())
+    }
+
+    /// Constructs a new `Instance<T>`, blocking the current thread until
+    /// doing so would not exceed the [limit](Tabulate::set_limit) of `T`.
+    ///
+    /// The thread is woken when an instance of `T` is dropped, so the census
+    /// can act as a semaphore for expensive objects. Waiters are admitted in
+    /// no particular order. When counting is [disabled](crate#disabling),
+    /// drops wake no one, and the thread only rechecks its admission
+    /// periodically.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use type_census::{Instance, Tabulate};
+    ///
+    /// #[derive(Tabulate)]
+    /// pub struct Worker {
+    ///     _instance: Instance<Self>,
+    /// }
+    ///
+    /// Worker::set_limit(1);
+    /// let busy = Worker { _instance: Instance::new_blocking() };
+    /// std::thread::spawn(move || {
+    ///     std::thread::sleep(Duration::from_millis(10));
+    ///     drop(busy);
+    /// });
+    ///
+    /// let _next = Worker { _instance: Instance::new_blocking() };
+    /// assert_eq!(Worker::instances(), 1);
+    /// ```
+    #[track_caller]
+    pub fn new_blocking() -> Self {
+        if let Ok(instance) = Self::try_new() {
+            return instance;
+        }
+        let waiter = Waiter::new::<T>();
+        waiter.register(&Waker::from(Arc::new(Unpark(std::thread::current()))));
+        loop {
+            if let Ok(instance) = Self::try_new() {
+                return instance;
+            }
+            std::thread::park_timeout(MAX_PARK);
+        }
+    }
+
+    /// Constructs a new `Instance<T>` once doing so would not exceed the
+    /// [limit](Tabulate::set_limit) of `T`, without blocking the current
+    /// thread.
+    ///
+    /// The task is woken when an instance of `T` is dropped, or the limit of
+    /// `T` changes. This is only available with the `async` feature, and
+    /// works with any executor. When counting is
+    /// [disabled](crate#disabling), drops wake no one.
+    ///
+    /// ```
+    /// use std::future::Future;
+    /// use std::pin::pin;
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::task::{Context, Poll, Wake, Waker};
+    /// use type_census::{Instance, Tabulate};
+    ///
+    /// #[derive(Tabulate)]
+    /// pub struct Session {
+    ///     _instance: Instance<Self>,
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct Flag(AtomicBool);
+    ///
+    /// impl Wake for Flag {
+    ///     fn wake(self: Arc<Self>) {
+    ///         self.0.store(true, Ordering::SeqCst);
+    ///     }
+    /// }
+    ///
+    /// Session::set_limit(1);
+    /// let first = Session { _instance: Instance::new() };
+    ///
+    /// let flag = Arc::new(Flag::default());
+    /// let waker = Waker::from(flag.clone());
+    /// let mut cx = Context::from_waker(&waker);
+    /// let mut admission = pin!(Instance::<Session>::new_async());
+    /// assert!(admission.as_mut().poll(&mut cx).is_pending());
+    ///
+    /// drop(first);
+    /// assert!(flag.0.load(Ordering::SeqCst));
+    /// let Poll::Ready(_second) = admission.as_mut().poll(&mut cx) else {
+    ///     panic!("admission should be ready");
+    /// };
+    /// assert_eq!(Session::instances(), 1);
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn new_async() -> Self {
+        let mut waiter = None;
+        std::future::poll_fn(|cx| {
+            if let Ok(instance) = Self::try_new() {
+                return std::task::Poll::Ready(instance);
+            }
+            waiter
+                .get_or_insert_with(Waiter::new::<T>)
+                .register(cx.waker());
+            Self::try_new().map_or(std::task::Poll::Pending, std::task::Poll::Ready)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counter::RelaxedCounter;
+
+    struct Limited;
+
+    impl Tabulate for Limited {
+        type Counter = RelaxedCounter;
+
+        fn counter() -> &'static RelaxedCounter {
+            static COUNTER: RelaxedCounter = RelaxedCounter::new();
+            &COUNTER
+        }
+    }
+
+    fn waiters<T: Tabulate>() -> usize {
+        gate::<T>().waiters.lock().unwrap().len()
+    }
+
+    #[test]
+    fn waiter_registers_once() {
+        struct Noop;
+
+        impl Wake for Noop {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        let waker = Waker::from(Arc::new(Noop));
+        let waiter = Waiter::new::<Limited>();
+        waiter.register(&waker);
+        waiter.register(&waker);
+        waiter.register(&Waker::from(Arc::new(Noop)));
+        assert_eq!(waiters::<Limited>(), 1);
+        drop(waiter);
+        assert_eq!(waiters::<Limited>(), 0);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn dropped_future_unregisters() {
+        use std::future::Future;
+        use std::task::Context;
+
+        struct Async;
+
+        impl Tabulate for Async {
+            type Counter = RelaxedCounter;
+
+            fn counter() -> &'static RelaxedCounter {
+                static COUNTER: RelaxedCounter = RelaxedCounter::new();
+                &COUNTER
+            }
+        }
+
+        Async::set_limit(0);
+        let waker = Waker::noop();
+        let mut cx = Context::from_waker(waker);
+        let mut admission = Box::pin(Instance::<Async>::new_async());
+        for _ in 0..3 {
+            assert!(admission.as_mut().poll(&mut cx).is_pending());
+        }
+        assert_eq!(waiters::<Async>(), 1);
+        drop(admission);
+        assert_eq!(waiters::<Async>(), 0);
+    }
+}
@@ -0,0 +1,109 @@
+//! Paired event counting, for populations that aren't tied to the lifetime
+//! of a Rust value.
+//!
+//! A [`Pair`] counts two kinds of events — "issued" and "resolved" — such as
+//! requests and responses, or acquires and releases. Its
+//! [imbalance](Pair::imbalance) is the number of issued events that have not
+//! yet been resolved.
+//!
+//! ## Example
+//! ```
+//! use type_census::pair::Pair;
+//!
+//! static REQUESTS: Pair = Pair::ZERO;
+//!
+//! REQUESTS.issue();
+//! REQUESTS.issue();
+//! REQUESTS.resolve();
+//!
+//! assert_eq!(REQUESTS.issued(), 2);
+//! assert_eq!(REQUESTS.resolved(), 1);
+//! assert_eq!(REQUESTS.imbalance(), 1);
+//! ```
+
+use crate::counter::{Counter, RelaxedCounter};
+use num_traits::identities::one;
+
+/// A pair of counters tracking issued and resolved events.
+///
+/// By default, this uses [`RelaxedCounter`] for both counts.
+pub struct Pair<C = RelaxedCounter>
+where
+    C: Counter,
+{
+    issued: C,
+    resolved: C,
+}
+
+impl<C> Pair<C>
+where
+    C: Counter,
+{
+    /// A fresh `Pair` in which no events have been issued or resolved.
+    pub const ZERO: Self = Self {
+        issued: C::ZERO,
+        resolved: C::ZERO,
+    };
+
+    /// Records the issue of one event.
+    #[inline(always)]
+    pub fn issue(&self) {
+        self.issued.add_assign(one());
+    }
+
+    /// Records the resolution of one event.
+    #[inline(always)]
+    pub fn resolve(&self) {
+        self.resolved.add_assign(one());
+    }
+
+    /// Eventually retrieve the number of events issued.
+    #[inline(always)]
+    pub fn issued(&self) -> C::Primitive {
+        self.issued.fetch()
+    }
+
+    /// Eventually retrieve the number of events resolved.
+    #[inline(always)]
+    pub fn resolved(&self) -> C::Primitive {
+        self.resolved.fetch()
+    }
+
+    /// Eventually retrieve the number of issued events not yet resolved.
+    ///
+    /// The issued and resolved counts are read separately, so under
+    /// concurrent updates the result may be transiently inaccurate.
+    #[inline(always)]
+    pub fn imbalance(&self) -> C::Primitive {
+        let resolved = self.resolved.fetch();
+        self.issued.fetch() - resolved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let pair = Pair::<RelaxedCounter>::ZERO;
+        assert_eq!(pair.imbalance(), 0);
+    }
+
+    #[test]
+    fn issue() {
+        let pair = Pair::<RelaxedCounter>::ZERO;
+        pair.issue();
+        assert_eq!(pair.issued(), 1);
+        assert_eq!(pair.imbalance(), 1);
+    }
+
+    #[test]
+    fn resolve() {
+        let pair = Pair::<RelaxedCounter>::ZERO;
+        pair.issue();
+        pair.resolve();
+        assert_eq!(pair.resolved(), 1);
+        assert_eq!(pair.imbalance(), 0);
+    }
+}
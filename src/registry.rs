@@ -0,0 +1,607 @@
+//! A global registry of tabulated types.
+//!
+//! Register each type you want to discover at runtime with
+//! [`Registry::register`]. With the `auto-register` feature, every
+//! non-generic type deriving [`Tabulate`] is instead registered
+//! automatically, at link time, and is present the first time
+//! [`registry()`] is called.
+//!
+//! With the `auto-register` feature, every type deriving [`Tabulate`],
+//! generic or not, is also listed in a [`manifest`] of the types tabulated
+//! by the program, which is complete before any of them is used.
+//!
+//! With the `reset` feature, every registered counter can be zeroed with
+//! [`census::reset_all`](crate::census::reset_all).
+//!
+//! ## Example
+//! ```
+//! use type_census::{registry, Instance, Tabulate};
+//!
+//! #[derive(Clone, Tabulate)]
+//! pub struct Foo {
+//!     _instance: Instance<Self>,
+//! }
+//!
+//! #[derive(Clone, Tabulate)]
+//! pub struct Bar<T> {
+//!     v: T,
+//!     _instance: Instance<Self>,
+//! }
+//!
+//! registry().register::<Foo>();
+//! registry().register::<Bar<u8>>();
+//! // all instantiations of `Bar` share a counter, so this is a no-op:
+//! registry().register::<Bar<i8>>();
+//!
+//! let _foo = Foo { _instance: Instance::new() };
+//! let _bars = vec![Bar { v: 0u8, _instance: Instance::new() }; 3];
+//!
+//! let mut counts: Vec<_> = registry().iter().collect();
+//! counts.sort();
+//! assert_eq!(
+//!     counts,
+//!     [
+//!         (concat!(module_path!(), "::Bar"), 3),
+//!         (concat!(module_path!(), "::Foo"), 1),
+//!     ]
+//! );
+//! ```
+
+use crate::counter::Counter;
+use crate::{Metadata, Tabulate};
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Produces a reference to the global [`Registry`].
+pub fn registry() -> &'static Registry {
+    static REGISTRY: Registry = Registry::new();
+    #[cfg(feature = "auto-register")]
+    {
+        static COLLECTED: std::sync::Once = std::sync::Once::new();
+        COLLECTED.call_once(|| {
+            for registration in inventory::iter::<Registration> {
+                (registration.register)(&REGISTRY);
+            }
+        });
+    }
+    &REGISTRY
+}
+
+/// A type to register in the global [`Registry`] at link time.
+///
+/// The `Tabulate` derive submits one of these for each non-generic type
+/// when the `auto-register` feature is enabled.
+#[cfg(feature = "auto-register")]
+#[doc(hidden)]
+pub struct Registration {
+    register: fn(&Registry),
+}
+
+#[cfg(feature = "auto-register")]
+impl Registration {
+    pub const fn new<T>() -> Self
+    where
+        T: Tabulate + 'static,
+    {
+        Self {
+            register: Registry::register::<T>,
+        }
+    }
+}
+
+#[cfg(feature = "auto-register")]
+inventory::collect!(Registration);
+
+/// Submits `$ty` for registration at link time.
+#[cfg(feature = "auto-register")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register {
+    ($ty:ty) => {
+        $crate::__private::inventory::submit! {
+            $crate::registry::Registration::new::<$ty>()
+        }
+    };
+}
+
+/// Submits `$ty` for registration at link time.
+#[cfg(not(feature = "auto-register"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register {
+    ($ty:ty) => {};
+}
+
+/// A type deriving [`Tabulate`], as listed in the [`manifest`].
+///
+/// The `Tabulate` derive submits one of these for every type, generic or
+/// not, when the `auto-register` feature is enabled.
+#[cfg(feature = "auto-register")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Declaration {
+    /// The name of the type, without its module path or generic parameters.
+    pub name: &'static str,
+    /// The path of the module in which the type is defined.
+    pub module_path: &'static str,
+    /// The source file in which the type is defined.
+    pub file: &'static str,
+    /// Whether the type is generic, and so is registered per instantiation,
+    /// if at all, rather than at link time.
+    pub generic: bool,
+    /// Tags attached to the type with `#[Tabulate(tags(...))]`.
+    pub tags: &'static [&'static str],
+    /// Key-value pairs attached to the type with `#[Tabulate(meta(...))]`.
+    pub meta: &'static [(&'static str, &'static str)],
+}
+
+#[cfg(feature = "auto-register")]
+impl Declaration {
+    #[doc(hidden)]
+    pub const fn new(
+        name: &'static str,
+        module_path: &'static str,
+        file: &'static str,
+        generic: bool,
+        tags: &'static [&'static str],
+        meta: &'static [(&'static str, &'static str)],
+    ) -> Self {
+        Self {
+            name,
+            module_path,
+            file,
+            generic,
+            tags,
+            meta,
+        }
+    }
+
+    /// Produces the path of the type: its module path and name.
+    pub fn path(&self) -> String {
+        format!("{}::{}", self.module_path, self.name)
+    }
+}
+
+#[cfg(feature = "auto-register")]
+inventory::collect!(Declaration);
+
+/// Produces every type deriving [`Tabulate`] in the program, sorted by
+/// path, whether or not it has been registered or instantiated.
+///
+/// The manifest is collected at link time, so it is complete as soon as the
+/// program starts; a program can, say, print it and exit when passed a
+/// flag, to audit instrumentation coverage or generate dashboards ahead of
+/// a deployment. See [`manifest_json`] for a machine-readable rendering.
+///
+/// ```
+/// use type_census::registry;
+/// use type_census::{Instance, Tabulate};
+///
+/// #[derive(Tabulate)]
+/// #[Tabulate(tags("net"))]
+/// pub struct Connection<T> {
+///     transport: T,
+///     _instance: Instance<Self>,
+/// }
+///
+/// let manifest = registry::manifest();
+/// let connection = manifest
+///     .iter()
+///     .find(|declaration| declaration.path().ends_with("::Connection"))
+///     .unwrap();
+/// assert!(connection.generic);
+/// assert_eq!(connection.tags, ["net"]);
+/// ```
+#[cfg(feature = "auto-register")]
+pub fn manifest() -> Vec<&'static Declaration> {
+    let mut manifest: Vec<_> = inventory::iter::<Declaration>.into_iter().collect();
+    manifest.sort_by_key(|declaration| (declaration.module_path, declaration.name));
+    manifest
+}
+
+/// Renders the [`manifest`] as JSON.
+///
+/// The output is an object with a `types` field: an array of objects with
+/// the `name` (the type path), `file`, `generic`, `tags` and `meta` of each
+/// type deriving [`Tabulate`], sorted by path.
+#[cfg(feature = "auto-register")]
+pub fn manifest_json() -> String {
+    use crate::snapshot::push_json_string;
+    let mut json = String::from(r#"{"types":["#);
+    for (i, declaration) in manifest().into_iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str(r#"{"name":"#);
+        push_json_string(&mut json, &declaration.path());
+        json.push_str(r#","file":"#);
+        push_json_string(&mut json, declaration.file);
+        json.push_str(&format!(r#","generic":{},"tags":["#, declaration.generic));
+        for (i, tag) in declaration.tags.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            push_json_string(&mut json, tag);
+        }
+        json.push_str(r#"],"meta":{"#);
+        for (i, (key, value)) in declaration.meta.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            push_json_string(&mut json, key);
+            json.push(':');
+            push_json_string(&mut json, value);
+        }
+        json.push_str("}}");
+    }
+    json.push_str("]}");
+    json
+}
+
+/// Submits the type named `$name` to the [`manifest`] at link time.
+#[cfg(feature = "auto-register")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare {
+    ($name:ident, $generic:expr, $tags:expr, $meta:expr) => {
+        $crate::__private::inventory::submit! {
+            $crate::registry::Declaration::new(
+                stringify!($name),
+                module_path!(),
+                file!(),
+                $generic,
+                $tags,
+                $meta,
+            )
+        }
+    };
+}
+
+/// Submits the type named `$name` to the manifest at link time.
+#[cfg(not(feature = "auto-register"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare {
+    ($name:ident, $generic:expr, $tags:expr, $meta:expr) => {};
+}
+
+/// A set of tabulated types, discoverable at runtime.
+///
+/// Obtain the global registry with [`registry()`].
+pub struct Registry {
+    inner: RwLock<Inner>,
+}
+
+struct Inner {
+    entries: Vec<Entry>,
+    /// The index in `entries` of each registered type, by type id.
+    type_ids: BTreeMap<TypeId, usize>,
+}
+
+/// A tabulated type, as recorded in a [`Registry`].
+#[derive(Clone, Copy, Debug)]
+pub struct Entry {
+    name: &'static str,
+    metadata: Metadata,
+    counter: usize,
+    count: fn() -> isize,
+    bytes: fn() -> Option<usize>,
+    peak_bytes: fn() -> Option<usize>,
+    underflows: fn() -> Option<u64>,
+    #[cfg(feature = "reset")]
+    reset: fn(),
+}
+
+impl Entry {
+    fn of<T>() -> Self
+    where
+        T: Tabulate,
+    {
+        let metadata = T::metadata();
+        let name = if metadata.module_path.is_empty() && metadata.param.is_none() {
+            metadata.name
+        } else {
+            Box::leak(metadata.path().into_boxed_str())
+        };
+        Self {
+            name,
+            metadata,
+            counter: T::counter() as *const T::Counter as usize,
+            count: count::<T>,
+            bytes: || T::counter().fetch_bytes(),
+            peak_bytes: || T::counter().fetch_peak_bytes(),
+            underflows: || T::counter().fetch_underflows(),
+            #[cfg(feature = "reset")]
+            reset: reset::<T>,
+        }
+    }
+
+    /// Produces the path of the type, e.g. `my_crate::net::Connection`.
+    ///
+    /// See [`Metadata::path`].
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Produces the metadata of the type, such as its size, alignment and
+    /// module path.
+    ///
+    /// For a generic type, this is the metadata of the instantiation that
+    /// was registered.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Produces the number of extant instances of the type.
+    pub fn count(&self) -> isize {
+        (self.count)()
+    }
+
+    /// Produces the number of bytes attributed to the extant instances of
+    /// the type, if its counter tracks bytes.
+    ///
+    /// See [`Counter::fetch_bytes`].
+    pub fn bytes(&self) -> Option<usize> {
+        (self.bytes)()
+    }
+
+    /// Produces the most bytes ever attributed to the instances of the type,
+    /// if its counter tracks bytes.
+    ///
+    /// See [`Counter::fetch_peak_bytes`].
+    pub fn peak_bytes(&self) -> Option<usize> {
+        (self.peak_bytes)()
+    }
+
+    /// Produces the number of times the counter of the type has been
+    /// decremented below zero, if it detects underflow.
+    ///
+    /// See [`Counter::fetch_underflows`].
+    pub fn underflows(&self) -> Option<u64> {
+        (self.underflows)()
+    }
+
+    /// Produces the address of the counter of the type, which identifies it
+    /// in side tables such as the recorders of [lifetimes](crate::lifetime).
+    pub(crate) fn counter(&self) -> usize {
+        self.counter
+    }
+
+    /// Zeroes the counter of the type.
+    #[cfg(feature = "reset")]
+    pub(crate) fn reset(&self) {
+        (self.reset)()
+    }
+}
+
+/// Produces the number of extant instances of `T`, saturated to `isize`.
+///
+/// See [`Counter::to_isize`].
+pub(crate) fn count<T>() -> isize
+where
+    T: Tabulate,
+{
+    T::Counter::to_isize(T::instances()).unwrap_or(0)
+}
+
+/// Produces `true` if `path` is within the module path `prefix`, matching
+/// whole path segments, as [`Registry::filter_prefix`] does.
+pub(crate) fn within(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix.trim_end_matches("::"))
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+/// Zeroes the counter of `T`.
+#[cfg(feature = "reset")]
+fn reset<T>()
+where
+    T: Tabulate,
+{
+    use crate::counter::Counter;
+    let counter = T::counter();
+    if counter.fetch_and_reset().is_err() {
+        counter.sub_assign(counter.fetch());
+    }
+}
+
+impl Registry {
+    const fn new() -> Self {
+        Self {
+            inner: RwLock::new(Inner {
+                entries: Vec::new(),
+                type_ids: BTreeMap::new(),
+            }),
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Inner> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Inner> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Registers `T`.
+    ///
+    /// Types sharing a counter (such as the instantiations of a generic type)
+    /// are registered once; registering a type again does nothing. `T` is
+    /// then known by its [`TypeId`]; see [`Registry::get`].
+    pub fn register<T>(&self)
+    where
+        T: Tabulate + 'static,
+    {
+        self.insert::<T>(Some(TypeId::of::<T>()));
+    }
+
+    /// Registers `T`, which is known by `type_id` if it is `'static`.
+    pub(crate) fn insert<T>(&self, type_id: Option<TypeId>)
+    where
+        T: Tabulate,
+    {
+        let counter = T::counter() as *const T::Counter as usize;
+        {
+            let inner = self.read();
+            let registered = match type_id {
+                Some(type_id) => inner.type_ids.contains_key(&type_id),
+                None => inner.entries.iter().any(|entry| entry.counter == counter),
+            };
+            if registered {
+                return;
+            }
+        }
+        let mut inner = self.write();
+        let index = match inner
+            .entries
+            .iter()
+            .position(|entry| entry.counter == counter)
+        {
+            Some(index) => index,
+            None => {
+                inner.entries.push(Entry::of::<T>());
+                inner.entries.len() - 1
+            }
+        };
+        if let Some(type_id) = type_id {
+            inner.type_ids.entry(type_id).or_insert(index);
+        }
+    }
+
+    /// Produces every registered type, in registration order.
+    pub fn entries(&self) -> impl Iterator<Item = Entry> {
+        self.read().entries.clone().into_iter()
+    }
+
+    /// Produces the registered type with the given [`TypeId`], if any.
+    ///
+    /// Each instantiation of a generic type is known by its type id only
+    /// once it has been registered with [`Registry::register`]. Types
+    /// registered by the `lazy-register` feature are known by their type id
+    /// only if they are not generic.
+    pub fn get(&self, type_id: TypeId) -> Option<Entry> {
+        let inner = self.read();
+        inner
+            .type_ids
+            .get(&type_id)
+            .map(|&index| inner.entries[index])
+    }
+
+    /// Produces every registered type satisfying `predicate`, in
+    /// registration order.
+    pub fn filter<P>(&self, mut predicate: P) -> impl Iterator<Item = Entry>
+    where
+        P: FnMut(&Entry) -> bool,
+    {
+        self.entries().filter(move |entry| predicate(entry))
+    }
+
+    /// Produces every registered type whose path is within the module path
+    /// `prefix`, in registration order.
+    ///
+    /// The prefix matches whole path segments: `my_crate::net` matches
+    /// `my_crate::net::Connection` and `my_crate::net::tcp::Stream`, but not
+    /// `my_crate::network::Packet`.
+    ///
+    /// ```
+    /// use type_census::{registry, snapshot::Snapshot, Instance, Tabulate};
+    ///
+    /// mod net {
+    ///     use type_census::{Instance, Tabulate};
+    ///
+    ///     #[derive(Tabulate)]
+    ///     pub struct Connection {
+    ///         pub _instance: Instance<Self>,
+    ///     }
+    /// }
+    ///
+    /// #[derive(Tabulate)]
+    /// pub struct Request {
+    ///     _instance: Instance<Self>,
+    /// }
+    ///
+    /// registry().register::<net::Connection>();
+    /// registry().register::<Request>();
+    ///
+    /// let _connection = net::Connection { _instance: Instance::new() };
+    ///
+    /// let report: Snapshot = registry()
+    ///     .filter_prefix(concat!(module_path!(), "::net"))
+    ///     .map(|entry| (entry.name(), entry.count()))
+    ///     .collect();
+    /// let connection = concat!(module_path!(), "::net::Connection");
+    /// assert_eq!(report.iter().collect::<Vec<_>>(), [(connection, 1)]);
+    /// ```
+    pub fn filter_prefix<'a>(&self, prefix: &'a str) -> impl Iterator<Item = Entry> + 'a {
+        self.entries()
+            .filter(move |entry| within(entry.name(), prefix))
+    }
+
+    /// Produces the path and number of extant instances of every registered
+    /// type, in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, isize)> {
+        self.entries().map(|entry| (entry.name(), entry.count()))
+    }
+
+    /// Produces the number of registered types.
+    pub fn len(&self) -> usize {
+        self.read().entries.len()
+    }
+
+    /// Produces `true` if no types are registered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counter::Counter;
+    use crate::Instance;
+
+    struct Foo<T> {
+        _v: T,
+        _instance: Instance<Self>,
+    }
+
+    impl<T> Tabulate for Foo<T> {
+        type Counter = crate::counter::RelaxedCounter;
+
+        fn counter() -> &'static Self::Counter {
+            static COUNTER: crate::counter::RelaxedCounter = Counter::ZERO;
+            &COUNTER
+        }
+    }
+
+    #[test]
+    fn register() {
+        let registry = Registry::new();
+        assert!(registry.is_empty());
+        registry.register::<Foo<u8>>();
+        registry.register::<Foo<i8>>();
+        assert_eq!(registry.len(), 1);
+
+        let _foo = Foo {
+            _v: (),
+            _instance: Instance::new(),
+        };
+        assert_eq!(
+            registry.iter().collect::<Vec<_>>(),
+            [("type_census::registry::tests::Foo", 1)]
+        );
+
+        assert_eq!(
+            registry
+                .get(TypeId::of::<Foo<i8>>())
+                .map(|entry| entry.name()),
+            Some("type_census::registry::tests::Foo")
+        );
+        assert!(registry.get(TypeId::of::<Foo<u16>>()).is_none());
+
+        let entry = registry.entries().next().unwrap();
+        assert_eq!(entry.metadata().module_path, "type_census::registry::tests");
+        assert_eq!(entry.metadata().size, 1);
+        assert_eq!(entry.metadata().align, 1);
+    }
+}
@@ -0,0 +1,195 @@
+//! Where the instances of a type were created.
+//!
+//! [`Instance::new`] is `#[track_caller]`. With the `creation-sites`
+//! feature, every instance is attributed to the source location that
+//! constructed it, and the instances of a type are counted per location.
+//! This is much cheaper than capturing a backtrace per instance, and is
+//! usually enough to find a leak.
+//!
+//! [`Instance`](crate::Instance) stays zero-sized, so a dropped instance
+//! can't be traced back to its site: sites count creations, not survivors.
+//! Compare them against the type's population, or [`clear`] them once the
+//! program reaches a steady state, so that they count only the instances
+//! created since.
+//!
+//! Nothing is counted when counting is [disabled](crate#disabling).
+//!
+//! Constructors that wrap [`Instance::new`], such as
+//! [`Instance::new_timed`], are `#[track_caller]` too. Wrap it in your own
+//! constructors with `#[track_caller]` to attribute instances to their
+//! callers instead.
+//!
+//! ## Example
+//! ```
+//! use type_census::{sites, Instance, Tabulate};
+//!
+//! #[derive(Clone, Tabulate)]
+//! pub struct Connection {
+//!     _instance: Instance<Self>,
+//! }
+//!
+//! let pool: Vec<Connection> = (0..3)
+//!     .map(|_| Connection { _instance: Instance::new() })
+//!     .collect();
+//! let _stray = Connection { _instance: Instance::new() };
+//!
+//! drop(pool);
+//!
+//! let sites = sites::of::<Connection>();
+//! assert_eq!(sites.total(), 1);
+//! let counts: Vec<_> = sites.iter().map(|(_, count)| count).collect();
+//! assert_eq!(counts, [3, 1]);
+//! // prints, e.g.:
+//! // my_crate::Connection: 1 live
+//! //   3 created at src/pool.rs:24
+//! //   1 created at src/pool.rs:26
+//! println!("{sites}");
+//! ```
+//!
+//! [`Instance::new`]: crate::Instance::new
+//! [`Instance::new_timed`]: crate::Instance::new_timed
+
+use crate::{registry, Tabulate};
+use std::collections::BTreeMap;
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{PoisonError, RwLock};
+
+/// The number of instances created at each creation site, of each type, by
+/// the address of its counter.
+type Table = BTreeMap<usize, BTreeMap<&'static Location<'static>, &'static AtomicU64>>;
+
+static SITES: RwLock<Table> = RwLock::new(BTreeMap::new());
+
+/// Produces the key of `T` in [`SITES`].
+///
+/// Types sharing a counter (such as the instantiations of a generic type)
+/// share their creation sites.
+fn key<T>() -> usize
+where
+    T: Tabulate,
+{
+    T::counter() as *const T::Counter as usize
+}
+
+/// Counts a new instance of `T` created at `location`.
+#[inline(always)]
+pub(crate) fn created<T>(location: &'static Location<'static>)
+where
+    T: Tabulate,
+{
+    if !cfg!(type_census_disabled) {
+        count::<T>(location);
+    }
+}
+
+fn count<T>(location: &'static Location<'static>)
+where
+    T: Tabulate,
+{
+    let key = key::<T>();
+    let site = SITES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&key)
+        .and_then(|sites| sites.get(location).copied());
+    let site = site.unwrap_or_else(|| {
+        let mut sites = SITES.write().unwrap_or_else(PoisonError::into_inner);
+        sites
+            .entry(key)
+            .or_default()
+            .entry(location)
+            .or_insert_with(|| Box::leak(Box::new(AtomicU64::new(0))))
+    });
+    let _ = site.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Forgets the creation sites of `T`, so that only the instances created
+/// from now on are counted.
+pub fn clear<T>()
+where
+    T: Tabulate,
+{
+    SITES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(&key::<T>());
+}
+
+/// The instances of a tabulated type, by creation site, as produced by
+/// [`of`].
+///
+/// Its [`Display`](std::fmt::Display) implementation renders the type's
+/// population, followed by each creation site's count, one per line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sites {
+    name: String,
+    total: isize,
+    sites: Vec<(&'static Location<'static>, u64)>,
+}
+
+impl Sites {
+    /// Produces the path of the type, e.g. `my_crate::net::Connection`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Produces the number of extant instances of the type.
+    ///
+    /// This is read from the type's counter, so it includes instances
+    /// counted without an [`Instance`](crate::Instance).
+    pub fn total(&self) -> isize {
+        self.total
+    }
+
+    /// Produces each creation site, and the number of instances created
+    /// there, in descending order of count.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static Location<'static>, u64)> + '_ {
+        self.sites.iter().copied()
+    }
+}
+
+impl std::fmt::Display for Sites {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}: {} live", self.name, self.total)?;
+        for (location, count) in &self.sites {
+            writeln!(
+                f,
+                "  {count} created at {}:{}",
+                location.file(),
+                location.line()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Produces the instances of `T`, by creation site.
+///
+/// Ties are broken by source location.
+pub fn of<T>() -> Sites
+where
+    T: Tabulate,
+{
+    let sites = by_counter(key::<T>());
+    Sites {
+        name: T::metadata().path(),
+        total: registry::count::<T>(),
+        sites,
+    }
+}
+
+/// Produces the creation sites of the type whose counter is at address
+/// `counter`, as [`Sites::iter`] does.
+pub(crate) fn by_counter(counter: usize) -> Vec<(&'static Location<'static>, u64)> {
+    let mut sites: Vec<_> = SITES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&counter)
+        .into_iter()
+        .flatten()
+        .map(|(&location, count)| (location, count.load(Ordering::Relaxed)))
+        .collect();
+    sites.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    sites
+}
@@ -0,0 +1,1421 @@
+//! Point-in-time views of the census.
+//!
+//! Take a [`Snapshot`] of every registered type with
+//! [`census::report`](crate::census::report).
+//!
+//! # Schema
+//!
+//! [`Snapshot::to_json`] and, with the `serde` feature, serialization write
+//! snapshots in a versioned format, so archived snapshots remain readable by
+//! later versions of this crate. The format carries its
+//! [`SCHEMA_VERSION`] in a `schema_version` field, and evolves by these
+//! rules:
+//!
+//! - Fields may be added, but are never removed or renamed within a
+//!   version. Readers ignore fields they do not know, and fields added after
+//!   a version's introduction are optional when reading it.
+//! - Any other change increments the version. Readers accept every version
+//!   up to their own, and reject later ones.
+//! - A missing `schema_version` is read as version 1.
+
+use crate::registry::Entry;
+use crate::Metadata;
+use std::collections::BTreeMap;
+
+/// The version of the [schema](self#schema) snapshots are written in.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The number of extant instances of each registered type, captured at
+/// roughly one point in time.
+///
+/// Counts are read one type after another, so under concurrent updates a
+/// snapshot may not correspond to a single instant.
+///
+/// Snapshots taken by [`census::report`](crate::census::report) also carry
+/// the [metadata](Snapshot::metadata) of each type, and the
+/// [bytes](Snapshot::bytes) of each type whose counter tracks them, and the
+/// [underflows](Snapshot::underflows) of each type whose counter detects
+/// them. Taken
+/// with [`ReportOptions::process_memory`](crate::census::ReportOptions::process_memory),
+/// they also carry the [memory use](Snapshot::process_memory) of the
+/// process.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    counts: Vec<(String, isize)>,
+    metadata: BTreeMap<String, TypeMetadata>,
+    bytes: BTreeMap<String, Bytes>,
+    underflows: BTreeMap<String, u64>,
+    process: Option<ProcessMemory>,
+}
+
+/// The bytes attributed to a type in a [`Snapshot`], by a counter that
+/// tracks them, such as a [`BytesCounter`](crate::counter::BytesCounter).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Bytes {
+    /// The number of bytes attributed to the extant instances of the type.
+    pub live: usize,
+    /// The most bytes ever attributed to the instances of the type.
+    pub peak: usize,
+}
+
+/// The memory use of the process when a [`Snapshot`] was taken, in bytes, so
+/// that the populations of types can be compared against it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ProcessMemory {
+    /// The resident set size of the process.
+    pub rss: u64,
+    /// The largest resident set size of the process so far.
+    pub peak_rss: u64,
+    /// The size of the data segment of the process, which includes its heap.
+    pub data: u64,
+}
+
+impl ProcessMemory {
+    /// Reads the memory use of the current process.
+    ///
+    /// This is read from `/proc/self/status`, so produces `None` on
+    /// platforms other than Linux, or if it can't be read.
+    pub fn current() -> Option<Self> {
+        if cfg!(target_os = "linux") {
+            Self::parse(&std::fs::read_to_string("/proc/self/status").ok()?)
+        } else {
+            None
+        }
+    }
+
+    /// Parses the memory use of a process from the contents of its
+    /// `/proc/<pid>/status`.
+    fn parse(status: &str) -> Option<Self> {
+        let field = |name: &str| -> Option<u64> {
+            let line = status.lines().find_map(|line| line.strip_prefix(name))?;
+            let kb = line.trim().strip_suffix("kB")?.trim().parse::<u64>().ok()?;
+            kb.checked_mul(1024)
+        };
+        Some(Self {
+            rss: field("VmRSS:")?,
+            peak_rss: field("VmHWM:")?,
+            data: field("VmData:")?,
+        })
+    }
+}
+
+/// The [`Metadata`] of a type in a [`Snapshot`], in owned form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct TypeMetadata {
+    /// The size of the type, in bytes.
+    pub size: usize,
+    /// The alignment of the type, in bytes.
+    pub align: usize,
+    /// The name of the counter type tracking instances of the type.
+    pub counter: String,
+    /// The tags attached to the type.
+    pub tags: Vec<String>,
+    /// The key-value pairs attached to the type.
+    pub meta: BTreeMap<String, String>,
+}
+
+impl From<&Metadata> for TypeMetadata {
+    fn from(metadata: &Metadata) -> Self {
+        Self {
+            size: metadata.size,
+            align: metadata.align,
+            counter: metadata.counter.to_owned(),
+            tags: metadata.tags.iter().map(|&tag| tag.to_owned()).collect(),
+            meta: metadata
+                .meta
+                .iter()
+                .map(|&(key, value)| (key.to_owned(), value.to_owned()))
+                .collect(),
+        }
+    }
+}
+
+impl TypeMetadata {
+    /// Appends this metadata to `json` as a JSON object.
+    fn push_json(&self, json: &mut String) {
+        json.push_str(&format!(
+            r#"{{"size":{},"align":{},"counter":"#,
+            self.size, self.align
+        ));
+        push_json_string(json, &self.counter);
+        json.push_str(r#","tags":["#);
+        for (i, tag) in self.tags.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            push_json_string(json, tag);
+        }
+        json.push_str(r#"],"meta":{"#);
+        for (i, (key, value)) in self.meta.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            push_json_string(json, key);
+            json.push(':');
+            push_json_string(json, value);
+        }
+        json.push_str("}}");
+    }
+}
+
+impl Snapshot {
+    /// Collects a snapshot of the given entries and their counts, with
+    /// their metadata.
+    pub(crate) fn of_entries(entries: impl IntoIterator<Item = (Entry, isize)>) -> Self {
+        let mut snapshot = Self::default();
+        for (entry, count) in entries {
+            snapshot.counts.push((entry.name().to_owned(), count));
+            snapshot
+                .metadata
+                .insert(entry.name().to_owned(), entry.metadata().into());
+            if let (Some(live), Some(peak)) = (entry.bytes(), entry.peak_bytes()) {
+                snapshot
+                    .bytes
+                    .insert(entry.name().to_owned(), Bytes { live, peak });
+            }
+            if let Some(underflows) = entry.underflows() {
+                snapshot
+                    .underflows
+                    .insert(entry.name().to_owned(), underflows);
+            }
+        }
+        snapshot
+    }
+
+    /// Produces the metadata of the type at `name`, if this snapshot
+    /// carries it.
+    ///
+    /// Snapshots taken by [`census::report`](crate::census::report) carry
+    /// the metadata of every type; snapshots collected from an iterator of
+    /// paths and counts carry none.
+    ///
+    /// ```
+    /// use type_census::{census, registry, Instance, Tabulate};
+    ///
+    /// #[derive(Tabulate)]
+    /// #[Tabulate(tags("net"))]
+    /// pub struct Foo {
+    ///     _instance: Instance<Self>,
+    /// }
+    ///
+    /// registry().register::<Foo>();
+    ///
+    /// let snapshot = census::report();
+    /// let metadata = snapshot.metadata(concat!(module_path!(), "::Foo")).unwrap();
+    /// assert_eq!(metadata.tags, ["net"]);
+    /// ```
+    pub fn metadata(&self, name: &str) -> Option<&TypeMetadata> {
+        self.metadata.get(name)
+    }
+
+    /// Produces the bytes attributed to the type at `name`, if this
+    /// snapshot carries them.
+    ///
+    /// Snapshots taken by [`census::report`](crate::census::report) carry
+    /// the bytes of every type whose counter tracks them.
+    ///
+    /// ```
+    /// use type_census::{census, registry, Instance, Tabulate};
+    ///
+    /// #[derive(Tabulate)]
+    /// #[Tabulate(Counter = "type_census::counter::BytesCounter<16>")]
+    /// pub struct Foo {
+    ///     _instance: Instance<Self>,
+    /// }
+    ///
+    /// registry().register::<Foo>();
+    ///
+    /// let foo = Foo { _instance: Instance::new() };
+    /// drop(foo);
+    ///
+    /// let snapshot = census::report();
+    /// let bytes = snapshot.bytes(concat!(module_path!(), "::Foo")).unwrap();
+    /// assert_eq!((bytes.live, bytes.peak), (0, 16));
+    /// ```
+    pub fn bytes(&self, name: &str) -> Option<Bytes> {
+        self.bytes.get(name).copied()
+    }
+
+    /// Produces the number of times the counter of the type at `name` had
+    /// been decremented below zero, if this snapshot carries it.
+    ///
+    /// Snapshots taken by [`census::report`](crate::census::report) carry
+    /// the underflows of every type whose counter detects them, such as a
+    /// [`DiagnosticCounter`](crate::counter::DiagnosticCounter).
+    ///
+    /// ```
+    /// use type_census::{census, registry, Instance, Tabulate};
+    ///
+    /// #[derive(Tabulate)]
+    /// #[Tabulate(Counter = "type_census::counter::DiagnosticCounter")]
+    /// pub struct Foo {
+    ///     _instance: Instance<Self>,
+    /// }
+    ///
+    /// registry().register::<Foo>();
+    ///
+    /// let snapshot = census::report();
+    /// assert_eq!(snapshot.underflows(concat!(module_path!(), "::Foo")), Some(0));
+    /// ```
+    pub fn underflows(&self, name: &str) -> Option<u64> {
+        self.underflows.get(name).copied()
+    }
+
+    /// Produces the memory use of the process when this snapshot was taken,
+    /// if it carries it.
+    ///
+    /// Snapshots taken by [`census::report_with`](crate::census::report_with)
+    /// with [`ReportOptions::process_memory`](crate::census::ReportOptions::process_memory)
+    /// carry it, on platforms where it can be
+    /// [read](ProcessMemory::current).
+    ///
+    /// ```
+    /// use type_census::census::{self, ReportOptions};
+    ///
+    /// let snapshot = census::report_with(ReportOptions::new().process_memory(true));
+    /// if cfg!(target_os = "linux") {
+    ///     assert!(snapshot.process_memory().unwrap().rss > 0);
+    /// }
+    /// assert_eq!(census::report().process_memory(), None);
+    /// ```
+    pub fn process_memory(&self) -> Option<ProcessMemory> {
+        self.process
+    }
+
+    /// Attaches the memory use of the process to this snapshot.
+    pub(crate) fn with_process_memory(self, process: Option<ProcessMemory>) -> Self {
+        Self { process, ..self }
+    }
+
+    /// Produces the number of extant instances of the type at `name`, if it
+    /// was registered when this snapshot was taken.
+    pub fn get(&self, name: &str) -> Option<isize> {
+        self.counts
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, count)| *count)
+    }
+
+    /// Produces the path and number of extant instances of each type in this
+    /// snapshot.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, isize)> {
+        self.counts
+            .iter()
+            .map(|(name, count)| (name.as_str(), *count))
+    }
+
+    /// Produces the number of types in this snapshot.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Produces `true` if this snapshot contains no types.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Sorts the types in this snapshot by `order`.
+    ///
+    /// Ties are broken by type path.
+    ///
+    /// ```
+    /// use type_census::snapshot::{Snapshot, SortBy};
+    ///
+    /// let before: Snapshot = [("a::Foo", 9), ("a::Bar", 1), ("a::Baz", 5)].into_iter().collect();
+    /// let mut after: Snapshot = [("a::Foo", 9), ("a::Bar", 4), ("a::Baz", 6)].into_iter().collect();
+    ///
+    /// let names = |snapshot: &Snapshot| snapshot.iter().map(|(name, _)| name.to_owned()).collect::<Vec<_>>();
+    ///
+    /// after.sort(SortBy::Count);
+    /// assert_eq!(names(&after), ["a::Foo", "a::Baz", "a::Bar"]);
+    ///
+    /// after.sort(SortBy::Name);
+    /// assert_eq!(names(&after), ["a::Bar", "a::Baz", "a::Foo"]);
+    ///
+    /// after.sort(SortBy::Delta(&before));
+    /// assert_eq!(names(&after), ["a::Bar", "a::Baz", "a::Foo"]);
+    /// ```
+    pub fn sort(&mut self, order: SortBy<'_>) {
+        match order {
+            SortBy::Count => self
+                .counts
+                .sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.cmp(b))),
+            SortBy::Name => self.counts.sort_by(|(a, _), (b, _)| a.cmp(b)),
+            SortBy::Delta(previous) => {
+                let previous = previous.index();
+                let delta = |name: &str, count: isize| {
+                    count.wrapping_sub(previous.get(name).copied().unwrap_or(0))
+                };
+                self.counts.sort_by(|(a, x), (b, y)| {
+                    delta(b, *y).cmp(&delta(a, *x)).then_with(|| a.cmp(b))
+                })
+            }
+        }
+    }
+
+    /// Consumes this snapshot, producing it [sorted](Snapshot::sort) by
+    /// `order`.
+    pub fn sorted(mut self, order: SortBy<'_>) -> Self {
+        self.sort(order);
+        self
+    }
+
+    /// Shortens this snapshot to its first `len` types.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        for (name, _) in self.counts.drain(len.min(self.counts.len())..) {
+            self.metadata.remove(&name);
+            self.bytes.remove(&name);
+            self.underflows.remove(&name);
+        }
+    }
+
+    /// Groups the types in this snapshot by crate and module, rolling up
+    /// counts at each level.
+    ///
+    /// ```
+    /// use type_census::snapshot::Snapshot;
+    ///
+    /// let snapshot: Snapshot = [
+    ///     ("app::net::tcp::Stream", 3),
+    ///     ("app::net::Connection", 12),
+    ///     ("app::Config", 1),
+    ///     ("log::Record", 4),
+    /// ]
+    /// .into_iter()
+    /// .collect();
+    ///
+    /// let tree = snapshot.by_module();
+    /// assert_eq!(tree.total(), 20);
+    /// assert_eq!(tree.get("app::net").map(|net| net.total()), Some(15));
+    /// assert_eq!(
+    ///     tree.to_string(),
+    ///     "\
+    /// app  16
+    ///   net  15
+    ///     tcp  3
+    ///       Stream  3
+    ///     Connection  12
+    ///   Config  1
+    /// log  4
+    ///   Record  4
+    /// "
+    /// );
+    /// ```
+    pub fn by_module(&self) -> Module {
+        let mut root = Module::default();
+        for (path, count) in self.iter() {
+            let (modules, name) = path.rsplit_once("::").unwrap_or(("", path));
+            let mut module = &mut root;
+            module.total = module.total.wrapping_add(count);
+            for segment in modules.split("::").filter(|segment| !segment.is_empty()) {
+                let index = match module.modules.iter().position(|m| m.name == segment) {
+                    Some(index) => index,
+                    None => {
+                        module.modules.push(Module {
+                            name: segment.to_owned(),
+                            ..Module::default()
+                        });
+                        module.modules.len() - 1
+                    }
+                };
+                module = &mut module.modules[index];
+                module.total = module.total.wrapping_add(count);
+            }
+            module.types.push((name.to_owned(), count));
+        }
+        root
+    }
+
+    /// Produces the total number of extant instances of the types in this
+    /// snapshot.
+    pub fn total(&self) -> isize {
+        self.iter()
+            .fold(0, |total, (_, count)| total.wrapping_add(count))
+    }
+
+    /// Produces the percentage of the [total](Snapshot::total) accounted
+    /// for by the type at `name`, if it is in this snapshot and the total is
+    /// positive.
+    pub fn share(&self, name: &str) -> Option<f64> {
+        Self::share_of(self.get(name)?, self.total())
+    }
+
+    fn share_of(count: isize, total: isize) -> Option<f64> {
+        (total > 0).then(|| count as f64 * 100.0 / total as f64)
+    }
+
+    /// Renders this snapshot as an aligned text table of type paths, counts
+    /// and percentages of the total, followed by a total row.
+    ///
+    /// This is equivalent to `self.to_string()`.
+    ///
+    /// ```
+    /// use type_census::snapshot::Snapshot;
+    ///
+    /// let snapshot: Snapshot = [("app::Foo", 3), ("app::net::Connection", 120)]
+    ///     .into_iter()
+    ///     .collect();
+    ///
+    /// assert_eq!(snapshot.total(), 123);
+    /// assert_eq!(
+    ///     snapshot.to_table(),
+    ///     "\
+    /// TYPE                  COUNT       %
+    /// app::Foo                  3    2.4%
+    /// app::net::Connection    120   97.6%
+    /// TOTAL                   123  100.0%
+    /// "
+    /// );
+    /// ```
+    pub fn to_table(&self) -> String {
+        self.to_string()
+    }
+
+    /// Renders this snapshot as JSON, without depending on `serde`.
+    ///
+    /// The output is an object with the `schema_version` of the
+    /// [schema](self#schema) and a `types` field: an array of objects with a
+    /// `name` (the type path), a `count`, and, if this snapshot carries
+    /// them, the type's `bytes`, `underflows` and `metadata`, in the order
+    /// of this snapshot. The bytes are an object with the `live` and `peak` bytes of
+    /// the type. The metadata is an object with the `size`, `align`,
+    /// `counter`, `tags` and `meta` of the type. If this snapshot carries the
+    /// [memory use](Snapshot::process_memory) of the process, it follows in
+    /// a `process` field: an object with the `rss`, `peak_rss` and `data`
+    /// of the process, in bytes. With the `serde` feature, snapshots
+    /// serialize to the same format.
+    ///
+    /// ```
+    /// use type_census::snapshot::Snapshot;
+    ///
+    /// let snapshot: Snapshot = [("app::Foo", 3), ("app::Bar", -1)].into_iter().collect();
+    ///
+    /// assert_eq!(
+    ///     snapshot.to_json(),
+    ///     concat!(
+    ///         r#"{"schema_version":1,"types":"#,
+    ///         r#"[{"name":"app::Foo","count":3},{"name":"app::Bar","count":-1}]}"#,
+    ///     )
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut json = format!(r#"{{"schema_version":{SCHEMA_VERSION},"types":["#);
+        for (i, (name, count)) in self.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            json.push_str(r#"{"name":"#);
+            push_json_string(&mut json, name);
+            json.push_str(&format!(r#","count":{count}"#));
+            if let Some(bytes) = self.bytes(name) {
+                json.push_str(&format!(
+                    r#","bytes":{{"live":{},"peak":{}}}"#,
+                    bytes.live, bytes.peak
+                ));
+            }
+            if let Some(underflows) = self.underflows(name) {
+                json.push_str(&format!(r#","underflows":{underflows}"#));
+            }
+            if let Some(metadata) = self.metadata(name) {
+                json.push_str(r#","metadata":"#);
+                metadata.push_json(&mut json);
+            }
+            json.push('}');
+        }
+        json.push(']');
+        if let Some(process) = self.process {
+            json.push_str(&format!(
+                r#","process":{{"rss":{},"peak_rss":{},"data":{}}}"#,
+                process.rss, process.peak_rss, process.data
+            ));
+        }
+        json.push('}');
+        json
+    }
+
+    /// Produces the per-type change in population from this snapshot to
+    /// `other`.
+    ///
+    /// A type missing from either snapshot is treated as having no extant
+    /// instances in it.
+    ///
+    /// ```
+    /// use type_census::{census, registry, Instance, Tabulate};
+    ///
+    /// #[derive(Tabulate)]
+    /// pub struct Foo {
+    ///     _instance: Instance<Self>,
+    /// }
+    ///
+    /// registry().register::<Foo>();
+    ///
+    /// let before = census::report();
+    /// let _foo = Foo { _instance: Instance::new() };
+    /// let after = census::report();
+    ///
+    /// let diff = before.diff(&after);
+    /// let foo = concat!(module_path!(), "::Foo");
+    /// assert_eq!(diff.get(foo), Some(1));
+    /// assert_eq!(diff.changed().collect::<Vec<_>>(), [(foo, 1)]);
+    /// ```
+    pub fn diff(&self, other: &Snapshot) -> Diff {
+        let before = self.index();
+        let after = other.index();
+        let mut counts: Vec<(String, isize, isize)> = self
+            .iter()
+            .map(|(name, count)| {
+                (
+                    name.to_owned(),
+                    count,
+                    after.get(name).copied().unwrap_or(0),
+                )
+            })
+            .collect();
+        counts.extend(
+            other
+                .iter()
+                .filter(|(name, _)| !before.contains_key(name))
+                .map(|(name, count)| (name.to_owned(), 0, count)),
+        );
+        Diff { counts }
+    }
+
+    /// Produces the count of each type in this snapshot, by path, for
+    /// repeated lookups.
+    fn index(&self) -> BTreeMap<&str, isize> {
+        self.iter().collect()
+    }
+}
+
+/// A module in a hierarchical census, produced by [`Snapshot::by_module`].
+///
+/// The root module has an empty name, and contains the crates.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Module {
+    name: String,
+    total: isize,
+    modules: Vec<Module>,
+    types: Vec<(String, isize)>,
+}
+
+impl Module {
+    /// Produces the name of this module, e.g. `net` for `my_crate::net`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Produces the number of extant instances of every type within this
+    /// module, including its submodules.
+    pub fn total(&self) -> isize {
+        self.total
+    }
+
+    /// Produces the submodules of this module.
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    /// Produces the name and number of extant instances of each type defined
+    /// directly in this module.
+    pub fn types(&self) -> impl Iterator<Item = (&str, isize)> {
+        self.types
+            .iter()
+            .map(|(name, count)| (name.as_str(), *count))
+    }
+
+    /// Produces the descendant module at `path`, relative to this module.
+    pub fn get(&self, path: &str) -> Option<&Module> {
+        path.split("::")
+            .filter(|segment| !segment.is_empty())
+            .try_fold(self, |module, segment| {
+                module.modules.iter().find(|m| m.name == segment)
+            })
+    }
+
+    /// Renders this module's contents as a [Graphviz](https://graphviz.org)
+    /// graph, in the DOT language.
+    ///
+    /// Each module is a box labeled with its name and total, with an edge to
+    /// each of its submodules and types. Each type is an ellipse labeled
+    /// with its name and count. This module itself is drawn only if it has a
+    /// name, so the root module renders as a forest of crates.
+    ///
+    /// ```
+    /// use type_census::snapshot::Snapshot;
+    ///
+    /// let snapshot: Snapshot = [("app::net::Connection", 12), ("app::Config", 1)]
+    ///     .into_iter()
+    ///     .collect();
+    ///
+    /// assert_eq!(
+    ///     snapshot.by_module().to_dot(),
+    ///     r#"digraph census {
+    ///   node [shape=box];
+    ///   "app" [label="app\n13"];
+    ///   "app::net" [label="net\n12"];
+    ///   "app" -> "app::net";
+    ///   "app::net::Connection" [label="Connection\n12", shape=ellipse];
+    ///   "app::net" -> "app::net::Connection";
+    ///   "app::Config" [label="Config\n1", shape=ellipse];
+    ///   "app" -> "app::Config";
+    /// }
+    /// "#
+    /// );
+    /// ```
+    ///
+    /// Render it with, e.g., `dot -Tsvg census.dot > census.svg`.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph census {\n  node [shape=box];\n");
+        if self.name.is_empty() {
+            self.push_dot(&mut dot, None);
+        } else {
+            dot.push_str(&format!(
+                "  {} [label={}];\n",
+                dot_string(&self.name),
+                dot_string(&format!("{}\n{}", self.name, self.total))
+            ));
+            self.push_dot(&mut dot, Some(&self.name));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Appends the nodes and edges of this module's contents to `dot`,
+    /// given this module's node id, if it is drawn.
+    fn push_dot(&self, dot: &mut String, id: Option<&str>) {
+        let child = |name: &str| match id {
+            Some(id) => format!("{id}::{name}"),
+            None => name.to_owned(),
+        };
+        let edge = |dot: &mut String, to: &str| {
+            if let Some(id) = id {
+                dot.push_str(&format!("  {} -> {};\n", dot_string(id), dot_string(to)));
+            }
+        };
+        for module in &self.modules {
+            let module_id = child(&module.name);
+            dot.push_str(&format!(
+                "  {} [label={}];\n",
+                dot_string(&module_id),
+                dot_string(&format!("{}\n{}", module.name, module.total))
+            ));
+            edge(dot, &module_id);
+            module.push_dot(dot, Some(&module_id));
+        }
+        for (name, count) in &self.types {
+            let type_id = child(name);
+            dot.push_str(&format!(
+                "  {} [label={}, shape=ellipse];\n",
+                dot_string(&type_id),
+                dot_string(&format!("{name}\n{count}"))
+            ));
+            edge(dot, &type_id);
+        }
+    }
+
+    fn fmt_indented(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+        for module in &self.modules {
+            writeln!(
+                f,
+                "{:indent$}{}  {}",
+                "",
+                module.name,
+                module.total,
+                indent = depth * 2
+            )?;
+            module.fmt_indented(f, depth + 1)?;
+        }
+        for (name, count) in &self.types {
+            writeln!(f, "{:indent$}{name}  {count}", "", indent = depth * 2)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Module {
+    /// Renders this module's contents as an indented tree, listing
+    /// submodules (with their totals) before types.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// An order in which to [sort](Snapshot::sort) a [`Snapshot`].
+#[derive(Clone, Copy, Debug)]
+pub enum SortBy<'a> {
+    /// By number of extant instances, descending.
+    Count,
+    /// By type path, ascending.
+    Name,
+    /// By change in number of extant instances since the given snapshot,
+    /// descending.
+    Delta(&'a Snapshot),
+}
+
+/// The per-type change in population between two [`Snapshot`]s.
+///
+/// Produce one with [`Snapshot::diff`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diff {
+    counts: Vec<(String, isize, isize)>,
+}
+
+impl Diff {
+    /// Produces the change in the number of extant instances of the type at
+    /// `name`, if it appears in either snapshot.
+    pub fn get(&self, name: &str) -> Option<isize> {
+        self.counts
+            .iter()
+            .find(|(n, ..)| n == name)
+            .map(|(_, before, after)| after.wrapping_sub(*before))
+    }
+
+    /// Produces the number of extant instances of the type at `name` in the
+    /// earlier and later snapshots, if it appears in either.
+    pub fn counts(&self, name: &str) -> Option<(isize, isize)> {
+        self.counts
+            .iter()
+            .find(|(n, ..)| n == name)
+            .map(|(_, before, after)| (*before, *after))
+    }
+
+    /// Produces the path, and the number of extant instances in the earlier
+    /// and later snapshots, of each type in this diff.
+    pub fn iter_counts(&self) -> impl Iterator<Item = (&str, isize, isize)> {
+        self.counts
+            .iter()
+            .map(|(name, before, after)| (name.as_str(), *before, *after))
+    }
+
+    /// Produces the path and change in population of each type in this diff.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, isize)> {
+        self.counts
+            .iter()
+            .map(|(name, before, after)| (name.as_str(), after.wrapping_sub(*before)))
+    }
+
+    /// Produces the path and change in population of each type whose
+    /// population changed.
+    pub fn changed(&self) -> impl Iterator<Item = (&str, isize)> {
+        self.iter().filter(|(_, delta)| *delta != 0)
+    }
+
+    /// Produces the path, and the number of extant instances in the earlier
+    /// and later snapshots, of each type whose growth exceeds the
+    /// thresholds of `options`, in descending order of growth.
+    ///
+    /// Ties are broken by type path. See [`RenderOptions`] for the
+    /// thresholds.
+    pub fn exceeding(&self, options: &RenderOptions) -> Vec<(&str, isize, isize)> {
+        let mut exceeding: Vec<_> = self
+            .iter_counts()
+            .filter(|&(_, before, after)| options.exceeds(before, after))
+            .collect();
+        exceeding.sort_by(|(a, a_before, a_after), (b, b_before, b_after)| {
+            let a_growth = a_after.wrapping_sub(*a_before);
+            let b_growth = b_after.wrapping_sub(*b_before);
+            b_growth.cmp(&a_growth).then_with(|| a.cmp(b))
+        });
+        exceeding
+    }
+
+    /// Renders a report of the types whose growth exceeds the thresholds of
+    /// `options`, in the format of `options`.
+    ///
+    /// As text, the report is an aligned table of type paths, their counts
+    /// in the earlier and later snapshots, and their growth, absolute and
+    /// relative; relative growth is `-` for types with no extant instances
+    /// in the earlier snapshot. As JSON, it is an object with a `types`
+    /// field: an array of objects with the `name`, `before`, `after` and
+    /// `growth` of each type. Either way, types are in the order of
+    /// [`Diff::exceeding`].
+    ///
+    /// ```
+    /// use type_census::snapshot::{Format, RenderOptions, Snapshot};
+    ///
+    /// let before: Snapshot = [("app::Foo", 100), ("app::Bar", 2), ("app::Baz", 7)]
+    ///     .into_iter()
+    ///     .collect();
+    /// let after: Snapshot = [("app::Foo", 104), ("app::Bar", 5), ("app::Baz", 6)]
+    ///     .into_iter()
+    ///     .collect();
+    /// let diff = before.diff(&after);
+    ///
+    /// let options = RenderOptions::new().min_growth(2).min_ratio(0.1);
+    /// assert_eq!(
+    ///     diff.render(&options),
+    ///     "\
+    /// TYPE      BEFORE  AFTER  GROWTH        %
+    /// app::Bar       2      5      +3  +150.0%
+    /// "
+    /// );
+    /// assert_eq!(
+    ///     diff.render(&options.format(Format::Json)),
+    ///     r#"{"types":[{"name":"app::Bar","before":2,"after":5,"growth":3}]}"#
+    /// );
+    ///
+    /// // e.g., in CI:
+    /// assert!(!diff.exceeding(&options).is_empty(), "the census grew");
+    /// ```
+    pub fn render(&self, options: &RenderOptions) -> String {
+        let exceeding = self.exceeding(options);
+        match options.format {
+            Format::Text => {
+                const NAME: &str = "TYPE";
+                const BEFORE: &str = "BEFORE";
+                const AFTER: &str = "AFTER";
+                const GROWTH: &str = "GROWTH";
+                const RATIO: &str = "%";
+                let rows: Vec<[String; 5]> = exceeding
+                    .iter()
+                    .map(|&(name, before, after)| {
+                        let growth = after.wrapping_sub(before);
+                        let ratio = if before > 0 {
+                            format!("{:+.1}%", growth as f64 * 100.0 / before as f64)
+                        } else {
+                            String::from("-")
+                        };
+                        [
+                            name.to_owned(),
+                            before.to_string(),
+                            after.to_string(),
+                            format!("{growth:+}"),
+                            ratio,
+                        ]
+                    })
+                    .collect();
+                let header = [NAME, BEFORE, AFTER, GROWTH, RATIO].map(String::from);
+                let mut widths = header.each_ref().map(String::len);
+                for row in &rows {
+                    for (width, cell) in widths.iter_mut().zip(row) {
+                        *width = (*width).max(cell.len());
+                    }
+                }
+                let mut report = String::new();
+                for [name, before, after, growth, ratio] in std::iter::once(header).chain(rows) {
+                    let [name_width, before_width, after_width, growth_width, ratio_width] = widths;
+                    report.push_str(&format!(
+                        "{name:<name_width$}  {before:>before_width$}  {after:>after_width$}  \
+                         {growth:>growth_width$}  {ratio:>ratio_width$}\n"
+                    ));
+                }
+                report
+            }
+            Format::Json => {
+                let mut json = String::from(r#"{"types":["#);
+                for (i, (name, before, after)) in exceeding.into_iter().enumerate() {
+                    if i > 0 {
+                        json.push(',');
+                    }
+                    json.push_str(r#"{"name":"#);
+                    push_json_string(&mut json, name);
+                    json.push_str(&format!(
+                        r#","before":{before},"after":{after},"growth":{}}}"#,
+                        after.wrapping_sub(before)
+                    ));
+                }
+                json.push_str("]}");
+                json
+            }
+        }
+    }
+
+    /// Produces the number of types in this diff.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Produces `true` if this diff contains no types.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+}
+
+/// Configuration for [`Diff::render`] and [`Diff::exceeding`].
+///
+/// A type exceeds the thresholds if it grew by at least
+/// [`min_growth`](RenderOptions::min_growth) instances, and by at least
+/// [`min_ratio`](RenderOptions::min_ratio) of its count in the earlier
+/// snapshot. Any growth of a type with no extant instances in the earlier
+/// snapshot meets the relative threshold.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderOptions {
+    format: Format,
+    min_growth: isize,
+    min_ratio: f64,
+}
+
+impl RenderOptions {
+    /// Constructs the default options, which render every type that grew as
+    /// text.
+    pub const fn new() -> Self {
+        Self {
+            format: Format::Text,
+            min_growth: 1,
+            min_ratio: 0.0,
+        }
+    }
+
+    /// Sets the format of the report.
+    ///
+    /// Defaults to [`Format::Text`].
+    pub const fn format(self, format: Format) -> Self {
+        Self { format, ..self }
+    }
+
+    /// Sets the least growth, in instances, for a type to be reported.
+    ///
+    /// Defaults to `1`. Thresholds below `1` are treated as `1`: only types
+    /// that grew are reported.
+    pub const fn min_growth(self, min_growth: isize) -> Self {
+        Self { min_growth, ..self }
+    }
+
+    /// Sets the least growth, as a fraction of the type's count in the
+    /// earlier snapshot, for a type to be reported; e.g., `0.1` for 10%.
+    ///
+    /// Defaults to `0.0`.
+    pub const fn min_ratio(self, min_ratio: f64) -> Self {
+        Self { min_ratio, ..self }
+    }
+
+    /// Produces `true` if growth from `before` to `after` exceeds these
+    /// thresholds.
+    fn exceeds(&self, before: isize, after: isize) -> bool {
+        let growth = after.wrapping_sub(before);
+        growth >= self.min_growth.max(1)
+            && (before <= 0 || growth as f64 >= self.min_ratio * before as f64)
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The format of a report rendered by [`Diff::render`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// An aligned, human-readable text table.
+    #[default]
+    Text,
+    /// A JSON object.
+    Json,
+}
+
+/// Quotes `s` as a DOT string, escaping quotes and backslashes, and turning
+/// newlines into centered line breaks.
+fn dot_string(s: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Appends `s` to `json` as a quoted, escaped JSON string.
+///
+/// This escapes exactly the characters `serde_json` does, in the same way,
+/// so both produce identical output.
+pub(crate) fn push_json_string(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            '\u{8}' => json.push_str("\\b"),
+            '\u{c}' => json.push_str("\\f"),
+            c if c < ' ' => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+impl std::fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const NAME: &str = "TYPE";
+        const COUNT: &str = "COUNT";
+        const SHARE: &str = "%";
+        const TOTAL: &str = "TOTAL";
+        let total = self.total();
+        let share = |count: isize| match Self::share_of(count, total) {
+            Some(share) => format!("{share:.1}%"),
+            None => String::from("-"),
+        };
+        let rows: Vec<(&str, String, String)> = self
+            .iter()
+            .chain(std::iter::once((TOTAL, total)))
+            .map(|(name, count)| (name, count.to_string(), share(count)))
+            .collect();
+        let name_width = rows
+            .iter()
+            .map(|(name, ..)| name.len())
+            .fold(NAME.len(), usize::max);
+        let count_width = rows
+            .iter()
+            .map(|(_, c, _)| c.len())
+            .fold(COUNT.len(), usize::max);
+        let share_width = rows
+            .iter()
+            .map(|(.., s)| s.len())
+            .fold(SHARE.len(), usize::max);
+        writeln!(
+            f,
+            "{NAME:<name_width$}  {COUNT:>count_width$}  {SHARE:>share_width$}"
+        )?;
+        for (name, count, share) in rows {
+            writeln!(
+                f,
+                "{name:<name_width$}  {count:>count_width$}  {share:>share_width$}"
+            )?;
+        }
+        if let Some(process) = self.process {
+            writeln!(
+                f,
+                "PROCESS RSS {} bytes (peak {} bytes), DATA {} bytes",
+                process.rss, process.peak_rss, process.data
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<S> FromIterator<(S, isize)> for Snapshot
+where
+    S: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (S, isize)>>(iter: I) -> Self {
+        Self {
+            counts: iter
+                .into_iter()
+                .map(|(name, count)| (name.into(), count))
+                .collect(),
+            metadata: BTreeMap::new(),
+            bytes: BTreeMap::new(),
+            underflows: BTreeMap::new(),
+            process: None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod schema {
+    //! The serialized form of snapshots, identical to [`Snapshot::to_json`].
+
+    use super::{Bytes, ProcessMemory, Snapshot, TypeMetadata, SCHEMA_VERSION};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct SnapshotRef<'a> {
+        schema_version: u32,
+        types: Vec<TypeRef<'a>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        process: Option<ProcessMemory>,
+    }
+
+    #[derive(Serialize)]
+    struct TypeRef<'a> {
+        name: &'a str,
+        count: isize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        bytes: Option<Bytes>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        underflows: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        metadata: Option<&'a TypeMetadata>,
+    }
+
+    #[derive(Deserialize)]
+    struct SnapshotOwned {
+        #[serde(default = "first_version")]
+        schema_version: u32,
+        types: Vec<TypeOwned>,
+        #[serde(default)]
+        process: Option<ProcessMemory>,
+    }
+
+    fn first_version() -> u32 {
+        1
+    }
+
+    #[derive(Deserialize)]
+    struct TypeOwned {
+        name: String,
+        count: isize,
+        #[serde(default)]
+        bytes: Option<Bytes>,
+        #[serde(default)]
+        underflows: Option<u64>,
+        #[serde(default)]
+        metadata: Option<TypeMetadata>,
+    }
+
+    impl Serialize for Snapshot {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            SnapshotRef {
+                schema_version: SCHEMA_VERSION,
+                types: self
+                    .iter()
+                    .map(|(name, count)| TypeRef {
+                        name,
+                        count,
+                        bytes: self.bytes(name),
+                        underflows: self.underflows(name),
+                        metadata: self.metadata(name),
+                    })
+                    .collect(),
+                process: self.process,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Snapshot {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let owned = SnapshotOwned::deserialize(deserializer)?;
+            if owned.schema_version > SCHEMA_VERSION {
+                return Err(D::Error::custom(format_args!(
+                    "unsupported snapshot schema version {} (at most {SCHEMA_VERSION} is supported)",
+                    owned.schema_version
+                )));
+            }
+            let mut snapshot = Snapshot {
+                process: owned.process,
+                ..Snapshot::default()
+            };
+            for ty in owned.types {
+                if let Some(metadata) = ty.metadata {
+                    snapshot.metadata.insert(ty.name.clone(), metadata);
+                }
+                if let Some(bytes) = ty.bytes {
+                    snapshot.bytes.insert(ty.name.clone(), bytes);
+                }
+                if let Some(underflows) = ty.underflows {
+                    snapshot.underflows.insert(ty.name.clone(), underflows);
+                }
+                snapshot.counts.push((ty.name, ty.count));
+            }
+            Ok(snapshot)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get() {
+        let snapshot: Snapshot = [("a::Foo", 1), ("a::Bar", 2)].into_iter().collect();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot.get("a::Bar"), Some(2));
+        assert_eq!(snapshot.get("a::Baz"), None);
+    }
+
+    #[test]
+    fn share() {
+        let snapshot: Snapshot = [("a::Foo", 1), ("a::Bar", 3)].into_iter().collect();
+        assert_eq!(snapshot.total(), 4);
+        assert_eq!(snapshot.share("a::Foo"), Some(25.0));
+        assert_eq!(snapshot.share("a::Baz"), None);
+
+        let empty: Snapshot = [("a::Foo", 0)].into_iter().collect();
+        assert_eq!(empty.share("a::Foo"), None);
+    }
+
+    #[test]
+    fn diff() {
+        let before: Snapshot = [("a::Foo", 1), ("a::Bar", 2)].into_iter().collect();
+        let after: Snapshot = [("a::Bar", 5), ("a::Baz", 3)].into_iter().collect();
+        let diff = before.diff(&after);
+        assert_eq!(
+            diff.iter().collect::<Vec<_>>(),
+            [("a::Foo", -1), ("a::Bar", 3), ("a::Baz", 3)]
+        );
+        assert_eq!(diff.counts("a::Baz"), Some((0, 3)));
+        assert_eq!(diff.get("a::Qux"), None);
+        assert!(before.diff(&before).changed().next().is_none());
+    }
+
+    #[test]
+    fn dot() {
+        let snapshot: Snapshot = [("a::b::Foo", 2), ("a::Bar\"", 1)].into_iter().collect();
+        let tree = snapshot.by_module();
+        assert_eq!(
+            tree.get("a::b").unwrap().to_dot(),
+            concat!(
+                "digraph census {\n  node [shape=box];\n",
+                "  \"b\" [label=\"b\\n2\"];\n",
+                "  \"b::Foo\" [label=\"Foo\\n2\", shape=ellipse];\n",
+                "  \"b\" -> \"b::Foo\";\n}\n",
+            )
+        );
+        assert!(tree
+            .to_dot()
+            .contains(r#""a::Bar\"" [label="Bar\"\n1", shape=ellipse];"#));
+    }
+
+    #[test]
+    fn thresholds() {
+        let before: Snapshot = [("a::Foo", 10), ("a::Bar", 0), ("a::Baz", -2)]
+            .into_iter()
+            .collect();
+        let after: Snapshot = [("a::Foo", 12), ("a::Bar", 1), ("a::Baz", 5), ("a::Qux", 3)]
+            .into_iter()
+            .collect();
+        let diff = before.diff(&after);
+        let names = |options: RenderOptions| {
+            diff.exceeding(&options)
+                .into_iter()
+                .map(|(name, ..)| name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(RenderOptions::new()),
+            ["a::Baz", "a::Qux", "a::Foo", "a::Bar"]
+        );
+        assert_eq!(
+            names(RenderOptions::new().min_growth(3)),
+            ["a::Baz", "a::Qux"]
+        );
+        assert_eq!(
+            names(RenderOptions::new().min_ratio(0.5)),
+            ["a::Baz", "a::Qux", "a::Bar"]
+        );
+        assert_eq!(names(RenderOptions::new().min_growth(-5)).len(), 4);
+        assert!(before
+            .diff(&before)
+            .exceeding(&RenderOptions::new())
+            .is_empty());
+        assert_eq!(
+            before.diff(&before).render(&RenderOptions::new()),
+            "TYPE  BEFORE  AFTER  GROWTH  %\n"
+        );
+    }
+
+    #[test]
+    fn json_escapes() {
+        let snapshot: Snapshot = [("a\"b\\c\n\u{1}", 0)].into_iter().collect();
+        assert_eq!(
+            snapshot.to_json(),
+            r#"{"schema_version":1,"types":[{"name":"a\"b\\c\n\u0001","count":0}]}"#
+        );
+    }
+
+    #[test]
+    fn json_metadata() {
+        let mut snapshot: Snapshot = [("a::Foo", 1), ("a::Bar", 2)].into_iter().collect();
+        snapshot.metadata.insert(
+            "a::Foo".to_owned(),
+            TypeMetadata {
+                size: 8,
+                align: 4,
+                counter: "RelaxedCounter".to_owned(),
+                tags: vec!["net".to_owned()],
+                meta: [("owner".to_owned(), "me".to_owned())].into(),
+            },
+        );
+        assert_eq!(
+            snapshot.to_json(),
+            concat!(
+                r#"{"schema_version":1,"types":[{"name":"a::Foo","count":1,"metadata":{"size":8,"align":4,"#,
+                r#""counter":"RelaxedCounter","tags":["net"],"meta":{"owner":"me"}}},"#,
+                r#"{"name":"a::Bar","count":2}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn process_memory() {
+        let status = "Name:\tfoo\nVmHWM:\t    2048 kB\nVmRSS:\t    1024 kB\nVmData:\t  512 kB\n";
+        assert_eq!(
+            ProcessMemory::parse(status),
+            Some(ProcessMemory {
+                rss: 1024 * 1024,
+                peak_rss: 2048 * 1024,
+                data: 512 * 1024,
+            })
+        );
+        assert_eq!(ProcessMemory::parse("VmRSS:\t1024 kB\n"), None);
+
+        let snapshot: Snapshot = [("a::Foo", 1)].into_iter().collect();
+        let snapshot = snapshot.with_process_memory(ProcessMemory::parse(status));
+        assert_eq!(
+            snapshot.to_json(),
+            concat!(
+                r#"{"schema_version":1,"types":[{"name":"a::Foo","count":1}],"#,
+                r#""process":{"rss":1048576,"peak_rss":2097152,"data":524288}}"#
+            )
+        );
+        assert!(snapshot
+            .to_table()
+            .ends_with("PROCESS RSS 1048576 bytes (peak 2097152 bytes), DATA 524288 bytes\n"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_matches_json() {
+        let mut snapshot: Snapshot = [("a\"b\\c\n\u{1}\u{8}\u{7f}", -3), ("a::Bar", 2)]
+            .into_iter()
+            .collect();
+        snapshot.metadata.insert(
+            "a::Bar".to_owned(),
+            TypeMetadata {
+                size: 0,
+                align: 1,
+                counter: "C".to_owned(),
+                tags: vec![],
+                meta: [("k".to_owned(), "v".to_owned())].into(),
+            },
+        );
+        snapshot
+            .bytes
+            .insert("a::Bar".to_owned(), Bytes { live: 4, peak: 9 });
+        snapshot.underflows.insert("a::Bar".to_owned(), 2);
+        snapshot.process = Some(ProcessMemory {
+            rss: 1,
+            peak_rss: 2,
+            data: 3,
+        });
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(json, snapshot.to_json());
+        assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), snapshot);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let before: Snapshot = [("a::Foo", 1)].into_iter().collect();
+        let after: Snapshot = [("a::Foo", 4)].into_iter().collect();
+        let json = serde_json::to_string(&before).unwrap();
+        assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), before);
+        let diff = before.diff(&after);
+        let json = serde_json::to_string(&diff).unwrap();
+        assert_eq!(serde_json::from_str::<Diff>(&json).unwrap(), diff);
+    }
+}
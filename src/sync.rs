@@ -0,0 +1,38 @@
+//! The synchronization primitives of [`DistributedCounter`] and
+//! [`PackedCounter`], swapped for [loom]'s models under `cfg(loom)`.
+//!
+//! [`DistributedCounter`]: crate::counter::DistributedCounter
+//! [`PackedCounter`]: crate::counter::PackedCounter
+//! [loom]: https://docs.rs/loom
+
+#[cfg(loom)]
+pub(crate) use loom::{hint, sync::atomic};
+#[cfg(not(loom))]
+pub(crate) use std::{hint, sync::atomic};
+
+/// Produces the number of the current thread; threads are numbered in order
+/// of first use.
+#[cfg(not(loom))]
+#[inline(always)]
+pub(crate) fn thread_number() -> usize {
+    use atomic::{AtomicUsize, Ordering};
+    static THREADS: AtomicUsize = AtomicUsize::new(0);
+    thread_local! {
+        static NUMBER: usize = THREADS.fetch_add(1, Ordering::Relaxed);
+    }
+    NUMBER.try_with(|number| *number).unwrap_or(0)
+}
+
+/// Produces the number of the current thread; threads are numbered in order
+/// of first use, in each execution of the model.
+#[cfg(loom)]
+pub(crate) fn thread_number() -> usize {
+    use atomic::{AtomicUsize, Ordering};
+    loom::lazy_static! {
+        static ref THREADS: AtomicUsize = AtomicUsize::new(0);
+    }
+    loom::thread_local! {
+        static NUMBER: usize = THREADS.fetch_add(1, Ordering::Relaxed);
+    }
+    NUMBER.try_with(|number| *number).unwrap_or(0)
+}
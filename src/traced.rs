@@ -0,0 +1,96 @@
+//! Per-instance [`tracing`] spans.
+
+use crate::{Instance, Tabulate};
+use tracing::Span;
+
+/// A guard that tracks the lifetime of an instance of `T`, like
+/// [`Instance<T>`], and keeps a [`Span`] open for as long as it lives.
+///
+/// Construct one with [`Instance::new_traced`]. Each long-lived object then
+/// appears as a span in tracing backends, from its construction to its drop.
+///
+/// ```
+/// use type_census::{Instance, Tabulate, TracedInstance};
+///
+/// #[derive(Tabulate)]
+/// pub struct Connection {
+///     id: u64,
+///     _instance: TracedInstance<Self>,
+/// }
+///
+/// impl Connection {
+///     pub fn new(id: u64) -> Self {
+///         Self {
+///             id,
+///             _instance: Instance::new_traced(tracing::info_span!("connection", id)),
+///         }
+///     }
+/// }
+///
+/// let connection = Connection::new(7);
+/// assert_eq!(Connection::instances(), 1);
+/// drop(connection);
+/// assert_eq!(Connection::instances(), 0);
+/// ```
+pub struct TracedInstance<T>
+where
+    T: Tabulate,
+{
+    instance: Instance<T>,
+    span: Span,
+}
+
+impl<T> TracedInstance<T>
+where
+    T: Tabulate,
+{
+    /// Produces the span tracking this instance.
+    ///
+    /// Enter it to attribute events to this instance.
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl<T> Instance<T>
+where
+    T: Tabulate,
+{
+    /// Constructs a new [`TracedInstance<T>`], which keeps `span` open until
+    /// it is dropped.
+    #[inline(always)]
+    #[track_caller]
+    pub fn new_traced(span: Span) -> TracedInstance<T> {
+        TracedInstance {
+            instance: Instance::new(),
+            span,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for TracedInstance<T>
+where
+    T: Tabulate,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("span", &self.span)
+            .finish()
+    }
+}
+
+impl<T> Clone for TracedInstance<T>
+where
+    T: Tabulate,
+{
+    /// Clones this guard, counting a new instance of `T` within the same
+    /// span.
+    #[inline(always)]
+    #[track_caller]
+    fn clone(&self) -> Self {
+        Self {
+            instance: self.instance.clone(),
+            span: self.span.clone(),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Weighted instance counting.
+
+use crate::counter::Counter;
+use crate::{census, Instance, Tabulate};
+
+/// The primitive type of the counter of `T`.
+type Primitive<T> = <<T as Tabulate>::Counter as Counter>::Primitive;
+
+/// A guard that tracks the lifetime of an instance of `T` that counts for
+/// `weight` instances.
+///
+/// Constructing a `WeightedInstance<T>` adds its weight to the population
+/// count of `T`, and dropping it subtracts its weight. This lets the census
+/// machinery tabulate aggregate quantities, such as buffered bytes or batch
+/// sizes, rather than just instances.
+///
+/// ```
+/// use type_census::{Instance, Tabulate, WeightedInstance};
+///
+/// #[derive(Tabulate)]
+/// pub struct Batch {
+///     items: Vec<u32>,
+///     _instance: WeightedInstance<Self>,
+/// }
+///
+/// impl Batch {
+///     fn new(items: Vec<u32>) -> Self {
+///         let weight = items.len() as isize;
+///         Self { items, _instance: Instance::with_weight(weight) }
+///     }
+/// }
+///
+/// let a = Batch::new(vec![1, 2, 3]);
+/// let b = Batch::new(vec![4, 5]);
+/// assert_eq!(Batch::instances(), 5);
+///
+/// drop(a);
+/// assert_eq!(Batch::instances(), 2);
+/// ```
+pub struct WeightedInstance<T>
+where
+    T: Tabulate,
+    Primitive<T>: Copy,
+{
+    weight: Primitive<T>,
+}
+
+impl<T> WeightedInstance<T>
+where
+    T: Tabulate,
+    Primitive<T>: Copy,
+{
+    /// Constructs a new `WeightedInstance<T>`, adding `weight` to the
+    /// population count of `T`.
+    #[inline(always)]
+    pub fn new(weight: Primitive<T>) -> Self {
+        #[cfg(feature = "lazy-register")]
+        T::ensure_registered();
+        census::add::<T>(weight, delta::<T>(weight));
+        Self { weight }
+    }
+
+    /// Produces the weight of this instance.
+    #[inline(always)]
+    pub fn weight(&self) -> Primitive<T> {
+        self.weight
+    }
+}
+
+impl<T> Instance<T>
+where
+    T: Tabulate,
+{
+    /// Constructs a new [`WeightedInstance<T>`], representing an instance of
+    /// `T` that counts for `weight` instances.
+    #[inline(always)]
+    pub fn with_weight(weight: Primitive<T>) -> WeightedInstance<T>
+    where
+        Primitive<T>: Copy,
+    {
+        WeightedInstance::new(weight)
+    }
+}
+
+/// Converts `weight` to a change in the population of `T`, for subscribers.
+#[inline(always)]
+fn delta<T>(weight: Primitive<T>) -> isize
+where
+    T: Tabulate,
+{
+    T::Counter::to_isize(weight).unwrap_or(0)
+}
+
+impl<T> std::fmt::Debug for WeightedInstance<T>
+where
+    T: Tabulate,
+    Primitive<T>: Copy + std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("weight", &self.weight)
+            .finish()
+    }
+}
+
+impl<T> Clone for WeightedInstance<T>
+where
+    T: Tabulate,
+    Primitive<T>: Copy,
+{
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self::new(self.weight)
+    }
+}
+
+impl<T> Drop for WeightedInstance<T>
+where
+    T: Tabulate,
+    Primitive<T>: Copy,
+{
+    #[inline(always)]
+    fn drop(&mut self) {
+        census::sub::<T>(self.weight, delta::<T>(self.weight));
+    }
+}
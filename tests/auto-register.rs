@@ -0,0 +1,44 @@
+#![cfg(feature = "auto-register")]
+
+use type_census::registry::{self, registry};
+use type_census::{Instance, Tabulate};
+
+#[derive(Tabulate)]
+pub struct Foo {
+    _instance: Instance<Self>,
+}
+
+#[derive(Tabulate)]
+pub struct Bar<T> {
+    _v: T,
+    _instance: Instance<Self>,
+}
+
+#[test]
+fn registered_at_link_time() {
+    let names: Vec<_> = registry().iter().map(|(name, _)| name).collect();
+    assert!(names.contains(&"auto_register::Foo"));
+    assert!(!names.contains(&"auto_register::Bar"));
+}
+
+#[test]
+fn declared_at_link_time() {
+    let manifest: Vec<_> = registry::manifest()
+        .into_iter()
+        .map(|declaration| (declaration.path(), declaration.generic))
+        .collect();
+    assert_eq!(
+        manifest,
+        [
+            ("auto_register::Bar".to_owned(), true),
+            ("auto_register::Foo".to_owned(), false),
+        ]
+    );
+    assert_eq!(
+        registry::manifest_json(),
+        concat!(
+            r#"{"types":[{"name":"auto_register::Bar","file":"tests/auto-register.rs","generic":true,"tags":[],"meta":{}},"#,
+            r#"{"name":"auto_register::Foo","file":"tests/auto-register.rs","generic":false,"tags":[],"meta":{}}]}"#
+        )
+    );
+}
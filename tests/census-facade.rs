@@ -0,0 +1,70 @@
+use type_census::{Instance, Tabulate};
+
+#[derive(Clone, Tabulate)]
+#[Tabulate(census, Counter = "type_census::counter::BytesCounter<16>")]
+pub struct Sized16 {
+    _instance: Instance<Self>,
+}
+
+#[derive(Clone, Tabulate)]
+#[Tabulate(census)]
+pub struct Watched {
+    _instance: Instance<Self>,
+}
+
+#[derive(Clone, Tabulate)]
+pub struct Unwatched {
+    _instance: Instance<Self>,
+}
+
+#[test]
+fn peak() {
+    let values = vec![
+        Sized16 {
+            _instance: Instance::new()
+        };
+        4
+    ];
+    drop(values);
+    assert_eq!(Sized16Census.peak(), Some(64));
+    assert_eq!(WatchedCensus.peak(), None);
+}
+
+#[test]
+fn bytes() {
+    let _value = Sized16 {
+        _instance: Instance::new(),
+    };
+    assert!(Sized16Census.bytes().unwrap() >= 16);
+    assert_eq!(WatchedCensus.bytes(), None);
+}
+
+#[cfg(not(type_census_disabled))]
+#[test]
+fn watch() {
+    use std::num::NonZeroUsize;
+    use std::sync::{Arc, Mutex};
+
+    let counts = Arc::new(Mutex::new(Vec::new()));
+    let subscription = WatchedCensus.watch(NonZeroUsize::new(2).unwrap(), {
+        let counts = counts.clone();
+        move |event| counts.lock().unwrap().push(event.count)
+    });
+
+    let watched = vec![
+        Watched {
+            _instance: Instance::new()
+        };
+        3
+    ];
+    let unwatched = vec![
+        Unwatched {
+            _instance: Instance::new()
+        };
+        3
+    ];
+    drop(subscription);
+    drop((watched, unwatched));
+
+    assert_eq!(*counts.lock().unwrap(), [2]);
+}
@@ -0,0 +1,52 @@
+#![cfg(type_census_disabled)]
+
+//! Run with `RUSTFLAGS="--cfg type_census_disabled"`.
+
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use type_census::counter::RelaxedCounter;
+use type_census::{census, Instance, Tabulate};
+
+#[derive(Clone, Tabulate)]
+pub struct Foo {
+    _instance: Instance<Self>,
+}
+
+#[derive(Clone, Tabulate)]
+#[Tabulate(Counter = "RelaxedCounter")]
+pub struct Bar {
+    _instance: Instance<Self>,
+}
+
+#[test]
+fn default_counter_counts_nothing() {
+    let _foos = vec![
+        Foo {
+            _instance: Instance::new()
+        };
+        3
+    ];
+    assert_eq!(Foo::instances(), 0);
+}
+
+#[test]
+fn named_counters_count_without_notifying() {
+    let events = Arc::new(AtomicUsize::new(0));
+    let subscription = census::subscribe(NonZeroUsize::MIN, {
+        let events = events.clone();
+        move |_| {
+            events.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+    let bars = vec![
+        Bar {
+            _instance: Instance::new()
+        };
+        3
+    ];
+    assert_eq!(Bar::instances(), 3);
+    drop(bars);
+    drop(subscription);
+    assert_eq!(events.load(Ordering::Relaxed), 0);
+}
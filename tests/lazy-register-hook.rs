@@ -0,0 +1,23 @@
+#![cfg(all(feature = "lazy-register", not(type_census_disabled)))]
+
+use type_census::{registry, Tabulate};
+
+#[derive(Tabulate)]
+#[Tabulate(hook = "drop")]
+pub struct Header<T> {
+    _len: T,
+}
+
+#[test]
+fn registered_on_first_tabulated() {
+    let registered = || {
+        registry()
+            .iter()
+            .any(|(name, _)| name == "lazy_register_hook::Header")
+    };
+    assert!(!registered());
+
+    let _header = Header { _len: 0u32 }.tabulated();
+    assert!(registered());
+    assert_eq!(Header::<u32>::instances(), 1);
+}
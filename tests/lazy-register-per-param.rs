@@ -0,0 +1,40 @@
+#![cfg(all(feature = "lazy-register", not(type_census_disabled)))]
+
+use type_census::{registry, Instance, Tabulate};
+
+#[derive(Tabulate)]
+#[Tabulate(per_param(C))]
+pub struct Message<C: 'static, P> {
+    _codec: C,
+    _payload: P,
+    _instance: Instance<Self>,
+}
+
+#[test]
+fn registered_per_instantiation() {
+    let _a = Message {
+        _codec: 0u8,
+        _payload: (),
+        _instance: Instance::new(),
+    };
+    let _b = Message {
+        _codec: 0u8,
+        _payload: 0,
+        _instance: Instance::new(),
+    };
+    let _c = Message {
+        _codec: 0i8,
+        _payload: (),
+        _instance: Instance::new(),
+    };
+
+    let mut names: Vec<_> = registry().iter().collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            ("lazy_register_per_param::Message<i8>", 1),
+            ("lazy_register_per_param::Message<u8>", 2),
+        ]
+    );
+}
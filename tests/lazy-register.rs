@@ -0,0 +1,29 @@
+#![cfg(feature = "lazy-register")]
+
+use type_census::{registry, Instance, Tabulate};
+
+#[derive(Clone, Tabulate)]
+pub struct Foo<T> {
+    _v: T,
+    _instance: Instance<Self>,
+}
+
+#[test]
+fn registered_on_first_instance() {
+    let registered = || {
+        registry()
+            .iter()
+            .any(|(name, _)| name == "lazy_register::Foo")
+    };
+    assert!(!registered());
+
+    let _foos = vec![
+        Foo {
+            _v: 0u8,
+            _instance: Instance::new(),
+        };
+        3
+    ];
+    assert!(registered());
+    assert_eq!(registry().len(), 1);
+}
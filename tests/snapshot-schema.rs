@@ -0,0 +1,95 @@
+#![cfg(feature = "serde")]
+
+//! Snapshots archived in each schema version remain readable.
+
+use type_census::snapshot::{Snapshot, SCHEMA_VERSION};
+
+fn read(fixture: &str) -> Result<Snapshot, serde_json::Error> {
+    let path = format!("{}/tests/fixtures/{fixture}", env!("CARGO_MANIFEST_DIR"));
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap())
+}
+
+#[test]
+fn v1() {
+    let snapshot = read("snapshot-v1.json").unwrap();
+    assert_eq!(
+        snapshot.iter().collect::<Vec<_>>(),
+        [("app::Foo", 3), ("app::Bar", -1)]
+    );
+    assert!(snapshot.metadata("app::Foo").is_none());
+    let metadata = snapshot.metadata("app::Bar").unwrap();
+    assert_eq!((metadata.size, metadata.align), (16, 8));
+    assert_eq!(metadata.counter, "type_census::counter::RelaxedCounter");
+    assert_eq!(metadata.tags, ["net"]);
+    assert_eq!(metadata.meta["owner"], "net-team");
+}
+
+#[test]
+fn v1_round_trips() {
+    assert_eq!(SCHEMA_VERSION, 1);
+    let fixture = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/snapshot-v1.json"
+    ))
+    .unwrap();
+    let snapshot = read("snapshot-v1.json").unwrap();
+    assert_eq!(snapshot.to_json(), fixture.trim_end());
+    assert_eq!(
+        serde_json::to_string(&snapshot).unwrap(),
+        fixture.trim_end()
+    );
+}
+
+#[test]
+fn unversioned_is_v1() {
+    let snapshot = read("snapshot-unversioned.json").unwrap();
+    assert_eq!(
+        snapshot.iter().collect::<Vec<_>>(),
+        [("app::Foo", 3), ("app::Bar", -1)]
+    );
+}
+
+#[test]
+fn v1_bytes() {
+    let fixture = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/snapshot-v1-bytes.json"
+    ))
+    .unwrap();
+    let snapshot = read("snapshot-v1-bytes.json").unwrap();
+    assert!(snapshot.bytes("app::Foo").is_none());
+    let bytes = snapshot.bytes("app::Bar").unwrap();
+    assert_eq!((bytes.live, bytes.peak), (1024, 4096));
+    assert_eq!(snapshot.to_json(), fixture.trim_end());
+}
+
+#[test]
+fn v1_process() {
+    let fixture = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/snapshot-v1-process.json"
+    ))
+    .unwrap();
+    let snapshot = read("snapshot-v1-process.json").unwrap();
+    let process = snapshot.process_memory().unwrap();
+    assert_eq!(
+        (process.rss, process.peak_rss, process.data),
+        (1048576, 2097152, 524288)
+    );
+    assert_eq!(snapshot.to_json(), fixture.trim_end());
+    assert_eq!(read("snapshot-v1.json").unwrap().process_memory(), None);
+}
+
+#[test]
+fn additions_are_ignored() {
+    assert_eq!(
+        read("snapshot-v1-additions.json").unwrap(),
+        read("snapshot-v1.json").unwrap()
+    );
+}
+
+#[test]
+fn later_versions_are_rejected() {
+    let error = read("snapshot-v2.json").unwrap_err();
+    assert!(error.to_string().contains("schema version 2"), "{error}");
+}
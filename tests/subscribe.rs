@@ -0,0 +1,70 @@
+#![cfg(not(type_census_disabled))]
+
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use type_census::{census, Instance, Tabulate};
+
+#[derive(Tabulate)]
+pub struct Foo {
+    _instance: Instance<Self>,
+}
+
+#[derive(Tabulate)]
+pub struct Bar {
+    _instance: Instance<Self>,
+}
+
+#[test]
+fn callbacks_may_subscribe() {
+    let inner = Arc::new(Mutex::new(Vec::new()));
+    let outer = census::subscribe(NonZeroUsize::MIN, {
+        let inner = inner.clone();
+        move |event| {
+            if event.metadata.name == "Foo" {
+                let subscription = census::subscribe(NonZeroUsize::MIN, |_| {});
+                inner.lock().unwrap().push(subscription);
+            }
+        }
+    });
+    drop(Foo {
+        _instance: Instance::new(),
+    });
+    drop(outer);
+    assert_eq!(inner.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn crossings_are_exact() {
+    let events = Arc::new(AtomicUsize::new(0));
+    let misreported = Arc::new(Mutex::new(Vec::new()));
+    let subscription = census::subscribe(NonZeroUsize::new(10).unwrap(), {
+        let events = events.clone();
+        let misreported = misreported.clone();
+        move |event| {
+            if event.metadata.name == "Bar" {
+                events.fetch_add(1, Ordering::Relaxed);
+                if event.count.rem_euclid(10) != 0 && event.count.rem_euclid(10) != 9 {
+                    misreported.lock().unwrap().push(event.count);
+                }
+            }
+        }
+    });
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..100 {
+                    let bars: Vec<_> = (0..25)
+                        .map(|_| Bar {
+                            _instance: Instance::new(),
+                        })
+                        .collect();
+                    drop(bars);
+                }
+            });
+        }
+    });
+    drop(subscription);
+    assert!(events.load(Ordering::Relaxed) > 0);
+    assert_eq!(*misreported.lock().unwrap(), []);
+}